@@ -1,60 +1,118 @@
 use eframe::egui;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
-use std::path::Path;
-use chrono::Local;
-
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, TryRecvError};
+
+use crate::audit::{self, AuditFinding, Severity};
+use crate::backend::{self, EnvBackend};
+use crate::config::Config;
+use crate::discovery::{self, Candidate};
+use crate::env_vars;
+use crate::hooks::{self, HookRun, SwitchOutcome};
 use crate::language::Language;
-use crate::types::{VersionEntry, CleanerEntry, IssueType, HistoryEntry};
-use crate::logic;
+use crate::layers::{self, LayeredEntry, Shadow};
+use crate::types::{VersionEntry, CleanerEntry, Hooks, InsertPosition, IssueType, PathChange, SwitchMode};
+use crate::user_env::{self, UserVar};
+use crate::logic::{self, ListValues, PathPreview};
+use crate::path_list::{self, PathList};
+use crate::pins;
+use crate::profiles;
+use crate::probe::{self, Probe, ProbeResult};
+use crate::shims;
+use crate::snapshots::{self, Snapshot};
 use crate::style;
+use crate::which::{self, WhichMatch};
+
+/// Eine Änderung, die erst nach Bestätigung in der Vorschau ausgeführt wird.
+enum PendingAction {
+    Switch(VersionEntry),
+    Clean,
+    Compact,
+    Profile(String),
+}
 
-#[derive(Deserialize, Serialize)]
-#[serde(default)]
-pub struct VersionSwitcherApp {
-    languages: HashMap<String, Vec<VersionEntry>>,
-    selected_group: String,
-    app_language: Language,
-    accent_color: [u8; 3],
+/// Was gerade im Hintergrund aktiviert wird, für Statuszeile und Verlauf danach.
+enum RunningSwitch {
+    Version { group: String, entry: VersionEntry },
+    Profile(String),
+    Project { dir: PathBuf, resolved: usize, problems: Vec<String> },
+}
 
-    history: Vec<HistoryEntry>,
+pub struct VersionSwitcherApp {
+    config: Config,
 
-    #[serde(skip)]
     new_group_name: String,
-    #[serde(skip)]
     new_path_input: String,
-    #[serde(skip)]
     new_alias_input: String,
-    #[serde(skip)]
     status_message: String,
 
-    #[serde(skip)]
     editing_index: Option<usize>,
-    #[serde(skip)]
     edit_name_buffer: String,
-    #[serde(skip)]
     edit_path_buffer: String,
+    edit_env_buffer: String,
+    edit_lists_buffer: String,
+    edit_hooks_buffer: String,
 
-    #[serde(skip)]
     show_cleaner_window: bool,
-    #[serde(skip)]
     cleaner_issues: Vec<CleanerEntry>,
+    /// Cleaner zeigt statt der Probleme die Sicherheitsprüfung.
+    cleaner_audit: bool,
+    audit_findings: Vec<AuditFinding>,
 
-    #[serde(skip)]
     show_history_window: bool,
 
-    #[serde(skip)]
+    pending_action: Option<(PendingAction, Vec<PathPreview>)>,
+
+    show_snapshots_window: bool,
+    snapshots: Vec<Snapshot>,
+    selected_snapshot: Option<usize>,
+
+    show_discover_window: bool,
+    discover_candidates: Vec<Candidate>,
+
+    show_env_window: bool,
+    user_vars: Vec<UserVar>,
+    /// Bearbeitete Variable. `env_selected` ist ihr Name, `None` bei einer neuen.
+    env_buffer: UserVar,
+    env_selected: Option<String>,
+
+    show_probe_window: bool,
+    probe_buffer: Probe,
+
+    show_hooks_window: bool,
+    /// Hooks der ausgewählten Gruppe als Zeilen (siehe `Hooks::to_lines`).
+    hooks_buffer: String,
+    /// Laufende Versionsabfragen (im Hintergrund-Thread).
+    probe_rx: Option<Receiver<(String, ProbeResult)>>,
+    probe_pending: HashSet<String>,
+
     search_query: String,
+
+    selected_profile: String,
+    new_profile_name: String,
+    new_target_var: String,
+
+    /// Wirksamer PATH und verdeckte Versionen, neu berechnet wenn sich PATH oder Modus ändern.
+    effective_path: Vec<LayeredEntry>,
+    shadows: HashMap<String, Vec<Shadow>>,
+    layers_key: Option<(ListValues, SwitchMode)>,
+    /// Profile, deren Versionen alle aktiv sind.
+    matching_profiles: Vec<String>,
+    /// Gesuchter Befehl und seine Treffer im wirksamen PATH (`None` = noch nicht gesucht).
+    which_query: String,
+    which_results: Option<Vec<WhichMatch>>,
+
+    /// Umschaltung, die samt Hooks im Hintergrund läuft.
+    running_switch: Option<(RunningSwitch, Receiver<SwitchOutcome>)>,
+
+    backend: Arc<dyn EnvBackend + Send + Sync>,
 }
 
 impl Default for VersionSwitcherApp {
     fn default() -> Self {
         Self {
-            languages: HashMap::new(),
-            selected_group: "General".to_owned(),
-            app_language: Language::German,
-            accent_color: [255, 140, 0],
-            history: Vec::new(),
+            config: Config::default(),
             new_group_name: String::new(),
             new_path_input: String::new(),
             new_alias_input: String::new(),
@@ -62,138 +120,487 @@ impl Default for VersionSwitcherApp {
             editing_index: None,
             edit_name_buffer: String::new(),
             edit_path_buffer: String::new(),
+            edit_env_buffer: String::new(),
+            edit_lists_buffer: String::new(),
+            edit_hooks_buffer: String::new(),
             show_cleaner_window: false,
             cleaner_issues: Vec::new(),
+            cleaner_audit: false,
+            audit_findings: Vec::new(),
             show_history_window: false,
+            pending_action: None,
+            show_snapshots_window: false,
+            snapshots: Vec::new(),
+            selected_snapshot: None,
+            show_discover_window: false,
+            show_env_window: false,
+            user_vars: Vec::new(),
+            env_buffer: UserVar::default(),
+            env_selected: None,
+            discover_candidates: Vec::new(),
+            show_probe_window: false,
+            show_hooks_window: false,
+            hooks_buffer: String::new(),
+            probe_buffer: Probe::default(),
+            probe_rx: None,
+            probe_pending: HashSet::new(),
             search_query: String::new(),
+            selected_profile: String::new(),
+            new_profile_name: String::new(),
+            new_target_var: String::new(),
+            effective_path: Vec::new(),
+            shadows: HashMap::new(),
+            layers_key: None,
+            matching_profiles: Vec::new(),
+            which_query: String::new(),
+            which_results: None,
+            running_switch: None,
+            backend: backend::default_backend().into(),
         }
     }
 }
 
 impl VersionSwitcherApp {
     pub fn new(cc: &eframe::CreationContext<'_>) -> Self {
-        let app: VersionSwitcherApp = if let Some(storage) = cc.storage {
-            eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default()
-        } else {
-            Default::default()
-        };
-        style::apply_style(&cc.egui_ctx, app.accent_color);
+        let mut app = VersionSwitcherApp::default();
+        if let Some(storage) = cc.storage {
+            app.config = eframe::get_value(storage, eframe::APP_KEY).unwrap_or_default();
+        }
+        style::apply_style(&cc.egui_ctx, app.config.accent_color);
         app
     }
 
-    fn add_to_history(&mut self, message: String) {
-        let time_str = Local::now().format("%H:%M:%S").to_string();
-        self.history.insert(0, HistoryEntry {
-            time: time_str,
-            message,
+    fn switch_version(&mut self, entry: &VersionEntry) {
+        let group = self.config.selected_group.clone();
+        let group_versions = self.config.languages.get(&group).cloned().unwrap_or_default();
+        let target = self.config.group_target(&group);
+        let mode = self.config.switch_mode;
+
+        let versions = vec![(group.clone(), entry.clone())];
+        let running = RunningSwitch::Version { group: group.clone(), entry: entry.clone() };
+        let entry = entry.clone();
+        self.start_switch(running, versions, move |backend| {
+            logic::activate(backend, mode, &group, &group_versions, &entry, &target)
         });
-        if self.history.len() > 100 {
-            self.history.pop();
-        }
     }
 
-    fn switch_version(&mut self, target_path: &str, target_alias: &str) {
-        let current_path_str = logic::get_current_path_var();
-        let mut parts: Vec<String> = current_path_str.split(';')
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string())
-            .collect();
+    /// Aktiviert sofort oder zeigt erst die Vorschau, je nach Einstellung.
+    fn request_switch(&mut self, entry: VersionEntry) {
+        if !self.config.confirm_changes {
+            self.switch_version(&entry);
+            return;
+        }
+        let group_versions = self.config.languages.get(&self.config.selected_group).cloned().unwrap_or_default();
+        let values = self.managed_values();
+        match logic::planned_lists(&values, self.config.switch_mode, &group_versions, &entry, &self.config.group_target(&self.config.selected_group)) {
+            Ok(planned) => self.pending_action = Some((PendingAction::Switch(entry), logic::preview_lists(&values, &planned, self.config.path_length_warning))),
+            Err(e) => self.status_message = self.config.app_language.status_error(&e),
+        }
+    }
 
-        if let Some(versions) = self.languages.get(&self.selected_group) {
-            for v in versions {
-                parts.retain(|p| !p.eq_ignore_ascii_case(&v.path));
-            }
+    fn request_cleaner(&mut self) {
+        if !self.config.confirm_changes {
+            self.run_cleaner();
+            return;
         }
+        let values = self.managed_values();
+        let (planned, _) = logic::cleanup_all(&values, &self.cleaner_issues);
+        self.pending_action = Some((PendingAction::Clean, logic::preview_lists(&values, &planned, self.config.path_length_warning)));
+    }
 
-        parts.insert(0, target_path.to_string());
-        let new_path_str = parts.join(";");
+    fn request_compact(&mut self) {
+        if !self.config.confirm_changes {
+            self.run_compact();
+            return;
+        }
+        let current = logic::get_current_path_var(self.backend.as_ref());
+        let planned = logic::compact_path(&current).path;
+        self.pending_action = Some((PendingAction::Compact, vec![logic::preview_path(&current, &planned, self.config.path_length_warning)]));
+    }
 
-        match logic::set_path_var(new_path_str) {
+    fn run_compact(&mut self) {
+        let current = logic::get_current_path_var(self.backend.as_ref());
+        let compaction = logic::compact_path(&current);
+        let saved = compaction.saved(&current);
+        match logic::set_path_var(self.backend.as_ref(), compaction.path, "Compact") {
             Ok(_) => {
-                logic::send_notification(
-                    self.app_language.notify_title(),
-                    &self.app_language.notify_body(target_alias)
-                );
-                self.status_message = self.app_language.status_activated(target_path);
-
-                let msg = format!("Activated: {} ({})", target_alias, self.selected_group);
-                self.add_to_history(msg);
+                self.status_message = self.config.app_language.status_compacted(compaction.duplicates, saved);
+                self.config.add_to_history(format!("Compacted PATH ({} characters saved)", saved));
             },
-            Err(e) => self.status_message = self.app_language.status_error(&e),
+            Err(e) => self.status_message = self.config.app_language.status_error(&e),
+        }
+    }
+
+    fn request_profile(&mut self, name: String) {
+        if !self.config.confirm_changes {
+            self.run_profile(&name);
+            return;
+        }
+        let Some(profile) = self.config.profiles.get(&name) else { return };
+        let values = self.managed_values();
+        match profiles::planned_lists(&values, self.config.switch_mode, profile, &self.config.languages, &self.config.group_targets()) {
+            Ok(planned) => {
+                let preview = logic::preview_lists(&values, &planned, self.config.path_length_warning);
+                self.pending_action = Some((PendingAction::Profile(name), preview));
+            }
+            Err(e) => self.status_message = self.config.app_language.status_error(&e),
+        }
+    }
+
+    fn run_profile(&mut self, name: &str) {
+        let lang = self.config.app_language;
+        let Some(profile) = self.config.profiles.get(name) else { return };
+        let versions = match profiles::resolve(profile, &self.config.languages) {
+            Ok(resolution) => resolution.resolved,
+            Err(e) => {
+                self.status_message = lang.status_error(&e);
+                return;
+            }
+        };
+        let profile = profile.clone();
+        let name = name.to_string();
+        let languages = self.config.languages.clone();
+        let targets = self.config.group_targets();
+        let mode = self.config.switch_mode;
+        self.start_switch(RunningSwitch::Profile(name.clone()), versions, move |backend| {
+            profiles::activate(backend, mode, &name, &profile, &languages, &targets)
+        });
+    }
+
+    fn save_profile(&mut self) {
+        let name = self.new_profile_name.trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+        let profile = profiles::from_current(self.config.switch_mode, &self.managed_values(), &self.config.languages, &self.config.group_targets());
+        self.status_message = self.config.app_language.status_profile_saved(&name, profile.len());
+        self.config.profiles.insert(name.clone(), profile);
+        self.config.add_to_history(format!("Saved profile: {}", name));
+        self.selected_profile = name;
+        self.new_profile_name.clear();
+        self.layers_key = None;
+    }
+
+    fn run_pending_action(&mut self) {
+        match self.pending_action.take() {
+            Some((PendingAction::Switch(entry), _)) => self.switch_version(&entry),
+            Some((PendingAction::Clean, _)) => self.run_cleaner(),
+            Some((PendingAction::Compact, _)) => self.run_compact(),
+            Some((PendingAction::Profile(name), _)) => self.run_profile(&name),
+            None => {},
         }
     }
 
     fn run_export(&mut self) {
-        match logic::export_to_file(&self.languages) {
+        match logic::export_to_file(&self.config.languages) {
             Ok(_) => {
-                self.status_message = self.app_language.status_export_ok().to_string();
-                self.add_to_history("Configuration Exported".to_string());
+                self.status_message = self.config.app_language.status_export_ok().to_string();
+                self.config.add_to_history("Configuration Exported".to_string());
             },
             Err(e) if e == "Cancelled" => {},
-            Err(e) => self.status_message = self.app_language.status_export_err(&e),
+            Err(e) => self.status_message = self.config.app_language.status_export_err(&e),
         }
     }
 
     fn run_import(&mut self) {
         match logic::import_from_file() {
             Ok(data) => {
-                self.languages = data;
-                if !self.languages.contains_key(&self.selected_group) {
-                    if let Some(key) = self.languages.keys().next() {
-                        self.selected_group = key.clone();
-                    } else {
-                        self.selected_group = "General".to_owned();
-                    }
-                }
-                self.status_message = self.app_language.status_import_ok().to_string();
-                self.add_to_history("Configuration Imported".to_string());
+                self.config.replace_groups(data);
+                self.status_message = self.config.app_language.status_import_ok().to_string();
+                self.config.add_to_history("Configuration Imported".to_string());
             },
             Err(e) if e == "Cancelled" => {},
-            Err(e) => self.status_message = self.app_language.status_import_err(&e),
+            Err(e) => self.status_message = self.config.app_language.status_import_err(&e),
         }
     }
 
-    fn run_cleaner(&mut self) {
-        let current = logic::get_current_path_var();
-        let (new_path, count) = logic::perform_cleanup(&current, &self.cleaner_issues);
+    fn run_project(&mut self) {
+        let Some(dir) = rfd::FileDialog::new().pick_folder() else { return };
+
+        let lang = self.config.app_language;
+        let pins = match pins::find_pins(&dir) {
+            Ok(p) if p.is_empty() => {
+                self.status_message = lang.status_no_pins().to_string();
+                return;
+            }
+            Ok(p) => p,
+            Err(e) => {
+                self.status_message = lang.status_error(&e);
+                return;
+            }
+        };
 
+        let resolution = pins::resolve_pins(&pins, &self.config.languages);
+        let problems: Vec<String> = resolution.problems.iter().map(|p| p.describe()).collect();
+
+        if resolution.resolved.is_empty() {
+            self.status_message = lang.status_project_activated(0);
+            if !problems.is_empty() {
+                self.status_message = format!("{} {}", self.status_message, problems.join("; "));
+            }
+            return;
+        }
+
+        let versions = resolution.resolved.clone();
+        let running = RunningSwitch::Project { dir, resolved: versions.len(), problems };
+        let languages = self.config.languages.clone();
+        let targets = self.config.group_targets();
+        let mode = self.config.switch_mode;
+        self.start_switch(running, versions, move |backend| {
+            pins::activate_resolution(backend, mode, &resolution, &languages, &targets, "Project")
+        });
+    }
+
+    /// Startet die Umschaltung samt Hooks im Hintergrund, außer es läuft schon eine.
+    fn start_switch(
+        &mut self,
+        running: RunningSwitch,
+        versions: Vec<(String, VersionEntry)>,
+        switch: impl FnOnce(&dyn EnvBackend) -> Result<(), String> + Send + 'static,
+    ) {
+        if self.running_switch.is_some() {
+            return;
+        }
+        self.status_message = self.config.app_language.status_switching().to_string();
+        let rx = hooks::spawn_with_hooks(self.backend.clone(), self.config.hooks.clone(), versions, switch);
+        self.running_switch = Some((running, rx));
+    }
+
+    /// Holt das Ergebnis einer laufenden Umschaltung ab.
+    fn poll_switch(&mut self, ctx: &egui::Context) {
+        let Some((_, rx)) = &self.running_switch else { return };
+        let (runs, result) = match rx.try_recv() {
+            Ok(done) => done,
+            Err(TryRecvError::Empty) => {
+                ctx.request_repaint_after(std::time::Duration::from_millis(100));
+                return;
+            }
+            Err(TryRecvError::Disconnected) => (Vec::new(), Err("Switch aborted".to_string())),
+        };
+        let Some((running, _)) = self.running_switch.take() else { return };
+
+        let lang = self.config.app_language;
+        let failed_hooks = self.record_hook_runs(&runs);
+        if let Err(e) = result {
+            self.status_message = lang.status_error(&e);
+            return;
+        }
+        match running {
+            RunningSwitch::Version { group, entry } => {
+                logic::send_notification(lang.notify_title(), &lang.notify_body(&entry.alias));
+                self.status_message = lang.status_activated(&entry.path);
+                self.config.add_to_history(format!("Activated: {} ({})", entry.alias, group));
+            }
+            RunningSwitch::Profile(name) => {
+                logic::send_notification(lang.notify_title(), &lang.notify_body(&name));
+                self.status_message = lang.status_profile_activated(&name);
+                self.config.add_to_history(format!("Activated profile: {}", name));
+            }
+            RunningSwitch::Project { dir, resolved, problems } => {
+                logic::send_notification(lang.notify_title(), &lang.notify_body(&dir.display().to_string()));
+                self.config.add_to_history(format!("Activated project: {}", dir.display()));
+                self.status_message = lang.status_project_activated(resolved);
+                if !problems.is_empty() {
+                    self.status_message = format!("{} {}", self.status_message, problems.join("; "));
+                }
+            }
+        }
+        self.append_hook_failures(failed_hooks);
+        // Im Shim-Modus ändert sich der PATH nicht, daher Ebenen und Profile neu berechnen
+        self.layers_key = None;
+    }
+
+    /// Schreibt die Hook-Läufe in den Verlauf und liefert, wie viele fehlgeschlagen sind.
+    fn record_hook_runs(&mut self, runs: &[HookRun]) -> usize {
+        for run in runs {
+            self.config.add_to_history(run.describe());
+        }
+        runs.iter().filter(|r| !r.succeeded()).count()
+    }
+
+    fn append_hook_failures(&mut self, failed: usize) {
+        if failed > 0 {
+            self.status_message = format!("{} {}", self.status_message, self.config.app_language.status_hooks_failed(failed));
+        }
+    }
+
+    fn run_discover_scan(&mut self) {
+        let mut roots = discovery::well_known_roots();
+        roots.extend(self.config.discovery_roots.iter().map(PathBuf::from));
+        self.discover_candidates = discovery::discover(&roots, &self.config.languages);
+    }
+
+    fn run_discover_add(&mut self) {
+        let count = discovery::add_selected(&mut self.config.languages, &self.discover_candidates);
         if count > 0 {
-            if let Ok(_) = logic::set_path_var(new_path) {
-                self.status_message = self.app_language.status_cleaned(count);
-                self.cleaner_issues = logic::scan_for_issues(&logic::get_current_path_var());
+            self.status_message = self.config.app_language.status_discovered(count);
+            self.config.add_to_history(format!("Discovered: {} versions added", count));
+        }
+        self.discover_candidates.retain(|c| !c.selected);
+    }
+
+    /// Holt fertige Versionsabfragen ab und startet neue für veraltete Einträge.
+    fn poll_probes(&mut self, ctx: &egui::Context) {
+        if let Some(rx) = &self.probe_rx {
+            loop {
+                match rx.try_recv() {
+                    Ok((dir, result)) => {
+                        self.probe_pending.remove(&dir);
+                        self.config.probe_cache.insert(dir, result);
+                    }
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.probe_rx = None;
+                        self.probe_pending.clear();
+                        break;
+                    }
+                }
+            }
+        }
+
+        if self.probe_rx.is_none() {
+            let jobs = self.config.stale_probes();
+            if !jobs.is_empty() {
+                self.probe_pending = jobs.iter().map(|(dir, _)| dir.clone()).collect();
+                self.probe_rx = Some(probe::spawn_probes(jobs));
+            }
+        }
+
+        if self.probe_rx.is_some() {
+            ctx.request_repaint_after(std::time::Duration::from_millis(100));
+        }
+    }
+
+    /// Aktuelle Werte aller Listen-Variablen, die Gruppen verwalten.
+    fn managed_values(&self) -> ListValues {
+        logic::read_lists(self.backend.as_ref(), &self.config.managed_vars())
+    }
+
+    fn refresh_layers(&mut self, values: &ListValues) {
+        let key = (values.clone(), self.config.switch_mode);
+        if self.layers_key.as_ref() == Some(&key) {
+            return;
+        }
+        let targets = self.config.group_targets();
+        self.effective_path = layers::current_effective(self.backend.as_ref());
+        self.shadows = layers::shadowed_versions(&self.effective_path, self.config.switch_mode, values, &self.config.languages, &targets);
+        self.matching_profiles = profiles::matching(&self.config.profiles, self.config.switch_mode, values, &self.config.languages, &targets);
+        self.layers_key = Some(key);
+        if self.which_results.is_some() {
+            self.run_which();
+        }
+    }
+
+    fn run_which(&mut self) {
+        let command = self.which_query.trim();
+        self.which_results = (!command.is_empty())
+            .then(|| which::resolve(&self.effective_path, command, &self.config.languages));
+    }
+
+    fn save_probe(&mut self, probe: Option<Probe>) {
+        // Laufende Abfragen verwerfen, sie gehören evtl. noch zur alten Definition
+        self.probe_rx = None;
+        self.probe_pending.clear();
+        let group = self.config.selected_group.clone();
+        self.config.set_probe(&group, probe);
+    }
 
-                self.add_to_history(format!("Cleaned {} entries from PATH", count));
-            } else {
-                self.status_message = "Error writing Path".to_owned();
+    fn run_restore(&mut self, idx: usize) {
+        let Some(snapshot) = self.snapshots.get(idx).cloned() else { return };
+        match snapshots::restore(self.backend.as_ref(), &snapshot) {
+            Ok(_) => {
+                self.status_message = self.config.app_language.status_restored(&snapshot.var, &snapshot.time);
+                self.config.add_to_history(format!("Restored {} from {}", snapshot.var, snapshot.time));
+                // Der Stand vor dem Zurückholen steht jetzt ganz oben
+                self.snapshots = snapshots::load(self.backend.as_ref());
+                self.selected_snapshot = None;
+            },
+            Err(e) => self.status_message = self.config.app_language.status_error(&e),
+        }
+    }
+
+    fn save_user_var(&mut self) {
+        let lang = self.config.app_language;
+        let mut var = self.env_buffer.clone();
+        var.name = var.name.trim().to_string();
+        match user_env::set_var(self.backend.as_ref(), &var.name, &var.value, var.expandable, "Edit variable") {
+            Ok(_) => {
+                self.status_message = lang.status_var_set(&var.name);
+                self.config.add_to_history(format!("Set variable: {}", var.describe()));
+                self.env_selected = Some(var.name.clone());
+                self.env_buffer = var;
+                self.user_vars = user_env::list(self.backend.as_ref());
+                // Es kann auch eine verwaltete Liste (z.B. PATH) gewesen sein
+                self.layers_key = None;
             }
+            Err(e) => self.status_message = lang.status_error(&e),
+        }
+    }
+
+    fn delete_user_var(&mut self) {
+        let lang = self.config.app_language;
+        let Some(name) = self.env_selected.take() else { return };
+        match user_env::remove_var(self.backend.as_ref(), &name, "Delete variable") {
+            Ok(_) => {
+                self.status_message = lang.status_var_removed(&name);
+                self.config.add_to_history(format!("Deleted variable: {}", name));
+                self.env_buffer = UserVar::default();
+                self.user_vars = user_env::list(self.backend.as_ref());
+                self.layers_key = None;
+            }
+            Err(e) => {
+                self.env_selected = Some(name);
+                self.status_message = lang.status_error(&e);
+            }
+        }
+    }
+
+    fn scan_cleaner_issues(&mut self) {
+        let values = self.managed_values();
+        self.cleaner_issues = logic::scan_all(self.config.switch_mode, &values, &self.config.languages, &self.config.group_targets());
+    }
+
+    fn run_cleaner(&mut self) {
+        match logic::run_cleaner(self.backend.as_ref(), &self.cleaner_issues) {
+            Ok(0) => {},
+            Ok(count) => {
+                self.status_message = self.config.app_language.status_cleaned(count);
+                self.scan_cleaner_issues();
+
+                self.config.add_to_history(format!("Cleaned {} entries from PATH", count));
+            },
+            Err(e) => self.status_message = self.config.app_language.status_error(&e),
         }
     }
 }
 
 impl eframe::App for VersionSwitcherApp {
     fn save(&mut self, storage: &mut dyn eframe::Storage) {
-        eframe::set_value(storage, eframe::APP_KEY, self);
+        eframe::set_value(storage, eframe::APP_KEY, &self.config);
     }
 
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
+        self.poll_probes(ctx);
+        self.poll_switch(ctx);
+
         if !ctx.input(|i| i.raw.dropped_files.is_empty()) {
             let dropped_files = ctx.input(|i| i.raw.dropped_files.clone());
             for file in dropped_files {
-                if let Some(path) = file.path {
-                    if path.is_dir() {
-                        self.new_path_input = path.display().to_string();
-                        if self.new_alias_input.is_empty() {
-                            if let Some(folder_name) = path.file_name() {
-                                self.new_alias_input = folder_name.to_string_lossy().to_string();
-                            }
-                        }
+                if let Some(path) = file.path
+                    && path.is_dir()
+                {
+                    self.new_path_input = path.display().to_string();
+                    if self.new_alias_input.is_empty()
+                        && let Some(folder_name) = path.file_name()
+                    {
+                        self.new_alias_input = folder_name.to_string_lossy().to_string();
                     }
                 }
             }
         }
 
         if self.show_cleaner_window {
-            let lang = self.app_language;
+            let lang = self.config.app_language;
             ctx.show_viewport_immediate(
                 egui::ViewportId::from_hash_of("cleaner_window"),
                 egui::ViewportBuilder::default()
@@ -203,33 +610,86 @@ impl eframe::App for VersionSwitcherApp {
                     assert!(class == egui::ViewportClass::Immediate, "Backend error");
                     egui::CentralPanel::default().show(ctx, |ui| {
                         ui.heading(lang.window_cleaner_title());
-                        if ui.button(lang.btn_scan()).clicked() {
-                            self.cleaner_issues = logic::scan_for_issues(&logic::get_current_path_var());
-                        }
+                        ui.horizontal(|ui| {
+                            if ui.selectable_label(!self.cleaner_audit, lang.label_cleanup_mode()).clicked() {
+                                self.cleaner_audit = false;
+                                self.scan_cleaner_issues();
+                            }
+                            if ui.selectable_label(self.cleaner_audit, lang.label_audit_mode()).clicked() {
+                                self.cleaner_audit = true;
+                                self.audit_findings = audit::audit_current(self.backend.as_ref());
+                            }
+                            ui.separator();
+                            if ui.button(lang.btn_scan()).clicked() {
+                                if self.cleaner_audit {
+                                    self.audit_findings = audit::audit_current(self.backend.as_ref());
+                                } else {
+                                    self.scan_cleaner_issues();
+                                }
+                            }
+                        });
                         ui.separator();
 
-                        if self.cleaner_issues.is_empty() {
+                        if self.cleaner_audit {
+                            if self.audit_findings.is_empty() {
+                                ui.label(lang.label_no_findings());
+                            } else {
+                                egui::ScrollArea::vertical().show(ui, |ui| {
+                                    for f in &self.audit_findings {
+                                        ui.horizontal(|ui| {
+                                            let color = match f.severity {
+                                                Severity::Critical => egui::Color32::RED,
+                                                Severity::Warning => egui::Color32::from_rgb(255, 165, 0),
+                                            };
+                                            ui.colored_label(color, format!("[{}]", lang.severity_name(f.severity)));
+                                            ui.label(format!("[{}] {}", lang.layer_name(f.layer), f.entry));
+                                            ui.weak(f.describe());
+                                        });
+                                    }
+                                });
+                                ui.separator();
+                                if ui.button(lang.btn_export_report()).clicked() {
+                                    match audit::export_to_file(&self.audit_findings) {
+                                        Ok(_) => self.status_message = lang.status_report_exported().to_string(),
+                                        Err(e) if e == "Cancelled" => {},
+                                        Err(e) => self.status_message = lang.status_error(&e),
+                                    }
+                                }
+                            }
+                        } else if self.cleaner_issues.is_empty() {
                             ui.label(lang.label_no_issues());
                         } else {
                             egui::ScrollArea::vertical().show(ui, |ui| {
                                 for entry in &mut self.cleaner_issues {
                                     ui.horizontal(|ui| {
-                                        ui.checkbox(&mut entry.selected, "");
-                                        match entry.issue {
-                                            IssueType::Missing => {
-                                                ui.colored_label(egui::Color32::RED, format!("[{}]", lang.issue_missing()));
-                                            }
-                                            IssueType::Duplicate => {
-                                                ui.colored_label(egui::Color32::YELLOW, format!("[{}]", lang.issue_duplicate()));
-                                            }
+                                        let fix = entry.issue.fix();
+                                        let checkbox = ui.add_enabled(fix.is_some(), egui::Checkbox::without_text(&mut entry.selected));
+                                        if let Some(fix) = &fix {
+                                            checkbox.on_hover_text(lang.fix_label(fix));
+                                        }
+                                        let color = match entry.issue {
+                                            IssueType::Missing | IssueType::UnresolvedVar { .. } => egui::Color32::RED,
+                                            IssueType::Duplicate | IssueType::Variant { .. } => egui::Color32::YELLOW,
+                                            IssueType::EmptyDir | IssueType::NoExecutables => egui::Color32::GRAY,
+                                            _ => egui::Color32::from_rgb(255, 165, 0),
+                                        };
+                                        let label = ui.colored_label(color, format!("[{}]", lang.issue_label(&entry.issue)));
+                                        if matches!(entry.issue, IssueType::Shadowed { .. }) {
+                                            label.on_hover_text(lang.tooltip_shadowed_issue());
+                                        }
+                                        if entry.var != logic::PATH_VAR {
+                                            ui.strong(&entry.var);
                                         }
                                         ui.label(&entry.path);
+                                        if let Some(detail) = lang.issue_detail(&entry.issue) {
+                                            ui.weak(detail);
+                                        }
                                     });
                                 }
                             });
                             ui.separator();
                             if ui.button(lang.btn_clean_selected()).clicked() {
-                                self.run_cleaner();
+                                self.request_cleaner();
                             }
                         }
                     });
@@ -241,7 +701,7 @@ impl eframe::App for VersionSwitcherApp {
         }
 
         if self.show_history_window {
-            let lang = self.app_language;
+            let lang = self.config.app_language;
             ctx.show_viewport_immediate(
                 egui::ViewportId::from_hash_of("history_window"),
                 egui::ViewportBuilder::default()
@@ -253,16 +713,16 @@ impl eframe::App for VersionSwitcherApp {
                         ui.heading(lang.window_history_title());
 
                         if ui.button(lang.btn_clear_history()).clicked() {
-                            self.history.clear();
+                            self.config.history.clear();
                         }
 
                         ui.separator();
 
-                        if self.history.is_empty() {
+                        if self.config.history.is_empty() {
                             ui.label(lang.label_no_history());
                         } else {
                             egui::ScrollArea::vertical().show(ui, |ui| {
-                                for entry in &self.history {
+                                for entry in &self.config.history {
                                     ui.horizontal(|ui| {
                                         ui.label(egui::RichText::new(&entry.time).monospace().weak());
                                         ui.label(&entry.message);
@@ -279,16 +739,365 @@ impl eframe::App for VersionSwitcherApp {
             );
         }
 
-        let current_sys_path_str = logic::get_current_path_var();
-        let current_sys_paths: Vec<String> = current_sys_path_str.split(';')
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string())
-            .collect();
+        if let Some((_, previews)) = &self.pending_action {
+            let lang = self.config.app_language;
+            let mut apply = false;
+            let mut cancel = false;
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of("preview_window"),
+                egui::ViewportBuilder::default()
+                    .with_title(lang.window_preview_title())
+                    .with_inner_size([600.0, 450.0]),
+                |ctx, class| {
+                    assert!(class == egui::ViewportClass::Immediate, "Backend error");
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading(lang.window_preview_title());
+                        ui.separator();
+
+                        egui::ScrollArea::vertical().max_height(340.0).show(ui, |ui| {
+                            for preview in previews {
+                                // Weitere Variablen bekommen eine Überschrift, der PATH bleibt wie gewohnt
+                                if preview.var != logic::PATH_VAR {
+                                    ui.label(egui::RichText::new(&preview.var).strong());
+                                }
+                                for warning in &preview.warnings {
+                                    ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", warning.describe()));
+                                }
+                                if preview.is_unchanged() {
+                                    ui.label(lang.label_var_unchanged(&preview.var));
+                                } else {
+                                    show_changes(ui, &preview.changes);
+                                }
+                            }
+                        });
+                        ui.separator();
+
+                        ui.horizontal(|ui| {
+                            if ui.button(format!("✔ {}", lang.btn_apply())).clicked() {
+                                apply = true;
+                            }
+                            if ui.button(format!("❌ {}", lang.tooltip_cancel())).clicked() {
+                                cancel = true;
+                            }
+                        });
+                    });
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        cancel = true;
+                    }
+                }
+            );
+            if apply {
+                self.run_pending_action();
+            } else if cancel {
+                self.pending_action = None;
+            }
+        }
+
+        if self.show_snapshots_window {
+            let lang = self.config.app_language;
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of("snapshots_window"),
+                egui::ViewportBuilder::default()
+                    .with_title(lang.window_snapshots_title())
+                    .with_inner_size([600.0, 500.0]),
+                |ctx, class| {
+                    assert!(class == egui::ViewportClass::Immediate, "Backend error");
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading(lang.window_snapshots_title());
+                        ui.separator();
+
+                        if self.snapshots.is_empty() {
+                            ui.label(lang.label_no_snapshots());
+                            return;
+                        }
+
+                        egui::ScrollArea::vertical().id_salt("snapshot_list").max_height(180.0).show(ui, |ui| {
+                            for (idx, snapshot) in self.snapshots.iter().enumerate() {
+                                let count = PathList::parse(&snapshot.value).len();
+                                let text = format!("{}  {}  {}  ({})", snapshot.time, snapshot.var, snapshot.reason, count);
+                                if ui.selectable_label(self.selected_snapshot == Some(idx), text).clicked() {
+                                    self.selected_snapshot = Some(idx);
+                                }
+                            }
+                        });
+                        ui.separator();
+
+                        let Some(idx) = self.selected_snapshot else { return };
+                        let Some(snapshot) = self.snapshots.get(idx) else { return };
+                        ui.label(lang.label_snapshot_diff());
+                        let current = logic::get_list_var(self.backend.as_ref(), &snapshot.var);
+                        egui::ScrollArea::vertical().id_salt("snapshot_diff").max_height(220.0).show(ui, |ui| {
+                            show_changes(ui, &logic::diff_paths(&current, &snapshot.value));
+                        });
+                        ui.separator();
+                        if ui.button(format!("⏪ {}", lang.btn_restore())).clicked() {
+                            self.run_restore(idx);
+                        }
+                    });
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        self.show_snapshots_window = false;
+                    }
+                }
+            );
+        }
+
+        if self.show_discover_window {
+            let lang = self.config.app_language;
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of("discover_window"),
+                egui::ViewportBuilder::default()
+                    .with_title(lang.window_discover_title())
+                    .with_inner_size([600.0, 450.0]),
+                |ctx, class| {
+                    assert!(class == egui::ViewportClass::Immediate, "Backend error");
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading(lang.window_discover_title());
+
+                        // Eigene Suchordner (zusätzlich zu den bekannten Orten)
+                        ui.label(lang.label_discover_roots());
+                        let mut remove_root = None;
+                        for (idx, root) in self.config.discovery_roots.iter().enumerate() {
+                            ui.horizontal(|ui| {
+                                if ui.small_button("🗑").on_hover_text(lang.tooltip_delete()).clicked() {
+                                    remove_root = Some(idx);
+                                }
+                                ui.monospace(root);
+                            });
+                        }
+                        if let Some(idx) = remove_root {
+                            self.config.discovery_roots.remove(idx);
+                        }
+
+                        ui.horizontal(|ui| {
+                            if ui.button(format!("📂 {}", lang.btn_add_root())).clicked()
+                                && let Some(path) = rfd::FileDialog::new().pick_folder()
+                            {
+                                self.config.discovery_roots.push(path.display().to_string());
+                            }
+                            if ui.button(lang.btn_scan()).clicked() {
+                                self.run_discover_scan();
+                            }
+                        });
+                        ui.separator();
+
+                        if self.discover_candidates.is_empty() {
+                            ui.label(lang.label_no_candidates());
+                        } else {
+                            egui::ScrollArea::vertical().max_height(250.0).show(ui, |ui| {
+                                for c in &mut self.discover_candidates {
+                                    ui.horizontal(|ui| {
+                                        ui.checkbox(&mut c.selected, "");
+                                        ui.label(egui::RichText::new(&c.group).strong());
+                                        ui.add(egui::TextEdit::singleline(&mut c.entry.alias).desired_width(80.0));
+                                        ui.label(egui::RichText::new(c.entry.dirs_string()).small().weak());
+                                    });
+                                }
+                            });
+                            ui.separator();
+                            if ui.button(lang.btn_add_selected()).clicked() {
+                                self.run_discover_add();
+                            }
+                        }
+                    });
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        self.show_discover_window = false;
+                    }
+                }
+            );
+        }
+
+        if self.show_env_window {
+            let lang = self.config.app_language;
+            let mut select = None;
+            let mut new_var = false;
+            let mut save = false;
+            let mut delete = false;
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of("env_window"),
+                egui::ViewportBuilder::default()
+                    .with_title(lang.window_env_title())
+                    .with_inner_size([650.0, 520.0]),
+                |ctx, class| {
+                    assert!(class == egui::ViewportClass::Immediate, "Backend error");
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading(lang.window_env_title());
+                        ui.separator();
+
+                        if self.user_vars.is_empty() {
+                            ui.label(lang.label_no_vars());
+                        } else {
+                            egui::ScrollArea::vertical().id_salt("env_list").max_height(260.0).show(ui, |ui| {
+                                for var in &self.user_vars {
+                                    ui.horizontal(|ui| {
+                                        // % markiert erweiterbare Werte
+                                        let marker = if var.expandable { '%' } else { ' ' };
+                                        let selected = self.env_selected.as_ref() == Some(&var.name);
+                                        let text = egui::RichText::new(format!("{} {}", marker, var.name)).monospace();
+                                        if ui.selectable_label(selected, text).clicked() {
+                                            select = Some(var.clone());
+                                        }
+                                        ui.label(egui::RichText::new(&var.value).small().weak());
+                                    });
+                                }
+                            });
+                        }
+                        ui.separator();
+
+                        egui::Grid::new("env_grid").num_columns(2).show(ui, |ui| {
+                            ui.label(lang.label_var_name());
+                            match &self.env_selected {
+                                Some(name) => { ui.strong(name); }
+                                None => { ui.add(egui::TextEdit::singleline(&mut self.env_buffer.name).hint_text("JAVA_HOME")); }
+                            }
+                            ui.end_row();
+                            ui.label(lang.label_var_value());
+                            ui.add(egui::TextEdit::singleline(&mut self.env_buffer.value).desired_width(f32::INFINITY));
+                            ui.end_row();
+                        });
+                        ui.checkbox(&mut self.env_buffer.expandable, lang.label_expandable())
+                            .on_hover_text(lang.tooltip_expandable());
+
+                        let check = user_env::validate_name(self.env_buffer.name.trim())
+                            .and_then(|_| user_env::validate_value(&self.env_buffer.value));
+                        if let Err(e) = &check
+                            && !self.env_buffer.name.trim().is_empty()
+                        {
+                            ui.colored_label(egui::Color32::RED, e);
+                        }
+                        ui.separator();
+
+                        ui.horizontal(|ui| {
+                            save = ui.add_enabled(check.is_ok(), egui::Button::new(format!("💾 {}", lang.tooltip_save()))).clicked();
+                            if self.env_selected.is_some() {
+                                delete = ui.button(format!("🗑 {}", lang.tooltip_delete())).clicked();
+                            }
+                            new_var = ui.button(format!("➕ {}", lang.btn_new_var())).clicked();
+                        });
+                    });
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        self.show_env_window = false;
+                    }
+                }
+            );
+            if let Some(var) = select {
+                self.env_selected = Some(var.name.clone());
+                self.env_buffer = var;
+            }
+            if new_var {
+                self.env_selected = None;
+                self.env_buffer = UserVar::default();
+            }
+            if save {
+                self.save_user_var();
+            }
+            if delete {
+                self.delete_user_var();
+            }
+        }
+
+        if self.show_hooks_window {
+            let lang = self.config.app_language;
+            let group = self.config.selected_group.clone();
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of("hooks_window"),
+                egui::ViewportBuilder::default()
+                    .with_title(lang.window_hooks_title())
+                    .with_inner_size([500.0, 260.0]),
+                |ctx, class| {
+                    assert!(class == egui::ViewportClass::Immediate, "Backend error");
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading(format!("{}: {}", lang.window_hooks_title(), group));
+                        ui.label(egui::RichText::new(lang.tooltip_hook_lines()).small().weak());
+                        ui.separator();
+
+                        ui.add(egui::TextEdit::multiline(&mut self.hooks_buffer)
+                            .hint_text("pre: ...\npost: ...")
+                            .desired_rows(5)
+                            .desired_width(f32::INFINITY));
+                        ui.separator();
+
+                        if ui.button(format!("💾 {}", lang.tooltip_save())).clicked() {
+                            let hooks = Hooks::parse_lines(&self.hooks_buffer);
+                            if hooks != self.config.hooks.get(&group).cloned().unwrap_or_default() {
+                                self.config.add_to_history(format!("Hooks changed: {}", group));
+                            }
+                            self.config.set_hooks(&group, hooks);
+                            self.show_hooks_window = false;
+                        }
+                    });
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        self.show_hooks_window = false;
+                    }
+                }
+            );
+        }
+
+        if self.show_probe_window {
+            let lang = self.config.app_language;
+            let group = self.config.selected_group.clone();
+            ctx.show_viewport_immediate(
+                egui::ViewportId::from_hash_of("probe_window"),
+                egui::ViewportBuilder::default()
+                    .with_title(lang.window_probe_title())
+                    .with_inner_size([400.0, 220.0]),
+                |ctx, class| {
+                    assert!(class == egui::ViewportClass::Immediate, "Backend error");
+                    egui::CentralPanel::default().show(ctx, |ui| {
+                        ui.heading(format!("{}: {}", lang.window_probe_title(), group));
+                        ui.separator();
+
+                        egui::Grid::new("probe_grid").num_columns(2).show(ui, |ui| {
+                            ui.label(lang.label_probe_executable());
+                            ui.add(egui::TextEdit::singleline(&mut self.probe_buffer.executable).hint_text("python"));
+                            ui.end_row();
+                            ui.label(lang.label_probe_args());
+                            ui.text_edit_singleline(&mut self.probe_buffer.args);
+                            ui.end_row();
+                            ui.label(lang.label_probe_regex());
+                            ui.text_edit_singleline(&mut self.probe_buffer.regex);
+                            ui.end_row();
+                        });
+                        ui.separator();
+
+                        ui.horizontal(|ui| {
+                            let valid = !self.probe_buffer.executable.trim().is_empty();
+                            if ui.add_enabled(valid, egui::Button::new(format!("💾 {}", lang.tooltip_save()))).clicked() {
+                                let probe = self.probe_buffer.clone();
+                                self.save_probe(Some(probe));
+                                self.config.add_to_history(format!("Probe set: {}", group));
+                                self.show_probe_window = false;
+                            }
+                            if self.config.probes.contains_key(&group) && ui.button(format!("🗑 {}", lang.btn_remove_probe())).clicked() {
+                                self.save_probe(None);
+                                self.config.add_to_history(format!("Probe removed: {}", group));
+                                self.show_probe_window = false;
+                            }
+                        });
+                    });
+                    if ctx.input(|i| i.viewport().close_requested()) {
+                        self.show_probe_window = false;
+                    }
+                }
+            );
+        }
+
+        let list_values = self.managed_values();
+        let current_sys_path_str = logic::list_value(&list_values, logic::PATH_VAR).to_string();
+        self.refresh_layers(&list_values);
+        // Die ausgewählte Gruppe kann statt PATH auch z.B. PYTHONPATH verwalten
+        let group_target = self.config.group_target(&self.config.selected_group);
+        let group_mode = logic::mode_for(self.config.switch_mode, &group_target);
+        let group_list = PathList::parse(logic::list_value(&list_values, &group_target.var));
+        // Im Shim-Modus zeigt das Manifest, welche Version aktiv ist
+        let shim_target = match group_mode {
+            SwitchMode::Path => None,
+            SwitchMode::Shims => shims::default_dir().and_then(|d| shims::active_target(&d, &self.config.selected_group)),
+        };
 
         egui::CentralPanel::default().show(ctx, |ui| {
             // Header
             ui.horizontal(|ui| {
-                let accent = egui::Color32::from_rgb(self.accent_color[0], self.accent_color[1], self.accent_color[2]);
+                let accent = egui::Color32::from_rgb(self.config.accent_color[0], self.config.accent_color[1], self.config.accent_color[2]);
                 ui.heading(egui::RichText::new(format!("Windows Version Switcher v{}", env!("CARGO_PKG_VERSION"))).color(accent));
 
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -296,49 +1105,83 @@ impl eframe::App for VersionSwitcherApp {
                     // --- RECHTE SEITE (Einstellungen) ---
                     egui::ComboBox::from_id_salt("app_lang_select")
                         .width(100.0)
-                        .selected_text(match self.app_language {
+                        .selected_text(match self.config.app_language {
                             Language::English => "🇺🇸 English",
                             Language::German => "🇩🇪 Deutsch",
                         })
                         .show_ui(ui, |ui| {
-                            ui.selectable_value(&mut self.app_language, Language::English, "🇺🇸 English");
-                            ui.selectable_value(&mut self.app_language, Language::German, "🇩🇪 Deutsch");
+                            ui.selectable_value(&mut self.config.app_language, Language::English, "🇺🇸 English");
+                            ui.selectable_value(&mut self.config.app_language, Language::German, "🇩🇪 Deutsch");
                         });
-                    ui.label(self.app_language.label_app_language());
+                    ui.label(self.config.app_language.label_app_language());
 
                     ui.add_space(10.0);
                     ui.separator();
                     ui.add_space(10.0);
 
                     // Import / Export
-                    if ui.button("📥").on_hover_text(self.app_language.tooltip_import()).clicked() {
+                    if ui.button("📥").on_hover_text(self.config.app_language.tooltip_import()).clicked() {
                         self.run_import();
                     }
-                    if ui.button("📤").on_hover_text(self.app_language.tooltip_export()).clicked() {
+                    if ui.button("📤").on_hover_text(self.config.app_language.tooltip_export()).clicked() {
                         self.run_export();
                     }
 
+                    // Projekt
+                    ui.add_space(5.0);
+                    if ui.button("📌").on_hover_text(self.config.app_language.tooltip_project()).clicked() {
+                        self.run_project();
+                    }
+
+                    // Discover
+                    ui.add_space(5.0);
+                    if ui.button("🔎").on_hover_text(self.config.app_language.tooltip_discover()).clicked() {
+                        self.show_discover_window = !self.show_discover_window;
+                        if self.show_discover_window {
+                            self.run_discover_scan();
+                        }
+                    }
+
                     // Cleaner
                     ui.add_space(5.0);
-                    if ui.button("🧹").on_hover_text(self.app_language.tooltip_cleaner()).clicked() {
+                    if ui.button("🧹").on_hover_text(self.config.app_language.tooltip_cleaner()).clicked() {
                         self.show_cleaner_window = !self.show_cleaner_window;
                         if self.show_cleaner_window {
-                            self.cleaner_issues = logic::scan_for_issues(&logic::get_current_path_var());
+                            self.scan_cleaner_issues();
+                        }
+                    }
+
+                    // Snapshots
+                    ui.add_space(5.0);
+                    if ui.button("⏪").on_hover_text(self.config.app_language.tooltip_snapshots()).clicked() {
+                        self.show_snapshots_window = !self.show_snapshots_window;
+                        if self.show_snapshots_window {
+                            self.snapshots = snapshots::load(self.backend.as_ref());
+                            self.selected_snapshot = None;
+                        }
+                    }
+
+                    // Umgebungsvariablen
+                    ui.add_space(5.0);
+                    if ui.button("🔧").on_hover_text(self.config.app_language.tooltip_env_editor()).clicked() {
+                        self.show_env_window = !self.show_env_window;
+                        if self.show_env_window {
+                            self.user_vars = user_env::list(self.backend.as_ref());
                         }
                     }
 
                     // History
                     ui.add_space(5.0);
-                    if ui.button("📜").on_hover_text(self.app_language.tooltip_history()).clicked() {
+                    if ui.button("📜").on_hover_text(self.config.app_language.tooltip_history()).clicked() {
                         self.show_history_window = !self.show_history_window;
                     }
 
                     // Color
                     ui.add_space(5.0);
-                    if egui::color_picker::color_edit_button_srgb(ui, &mut self.accent_color).changed() {
-                        style::apply_style(ctx, self.accent_color);
+                    if egui::color_picker::color_edit_button_srgb(ui, &mut self.config.accent_color).changed() {
+                        style::apply_style(ctx, self.config.accent_color);
                     }
-                    ui.label("🎨").on_hover_text(self.app_language.tooltip_accent_color());
+                    ui.label("🎨").on_hover_text(self.config.app_language.tooltip_accent_color());
                 });
             });
 
@@ -348,46 +1191,183 @@ impl eframe::App for VersionSwitcherApp {
             let mut delete_group_clicked = false;
 
             ui.horizontal(|ui| {
-                ui.label(self.app_language.label_group_select());
+                ui.label(self.config.app_language.label_group_select());
                 egui::ComboBox::from_id_salt("group_select")
-                    .selected_text(egui::RichText::new(&self.selected_group).strong())
+                    .selected_text(egui::RichText::new(&self.config.selected_group).strong())
                     .width(150.0)
                     .show_ui(ui, |ui| {
-                        for lang in self.languages.keys() {
-                            ui.selectable_value(&mut self.selected_group, lang.clone(), lang);
+                        for lang in self.config.languages.keys() {
+                            ui.selectable_value(&mut self.config.selected_group, lang.clone(), lang);
                         }
                     });
 
                 ui.add_space(10.0);
                 ui.text_edit_singleline(&mut self.new_group_name)
-                    .on_hover_text(self.app_language.tooltip_new_group());
+                    .on_hover_text(self.config.app_language.tooltip_new_group());
 
-                if ui.button(self.app_language.btn_new_group()).clicked() {
-                    if !self.new_group_name.is_empty() {
-                        self.languages.entry(self.new_group_name.clone()).or_insert_with(Vec::new);
-                        self.add_to_history(format!("Created Group: {}", self.new_group_name));
-                        self.selected_group = self.new_group_name.clone();
-                        self.new_group_name.clear();
-                    }
+                if ui.button(self.config.app_language.btn_new_group()).clicked() && !self.new_group_name.is_empty() {
+                    self.config.languages.entry(self.new_group_name.clone()).or_default();
+                    self.config.add_to_history(format!("Created Group: {}", self.new_group_name));
+                    self.config.selected_group = self.new_group_name.clone();
+                    self.new_group_name.clear();
                 }
 
                 ui.add_space(5.0);
-                if ui.button("🗑").on_hover_text(self.app_language.tooltip_delete_group()).clicked() {
+                if ui.button("🔬").on_hover_text(self.config.app_language.tooltip_probe()).clicked() {
+                    self.probe_buffer = self.config.probes.get(&self.config.selected_group).cloned().unwrap_or_default();
+                    self.show_probe_window = !self.show_probe_window;
+                }
+                if ui.button("⚡").on_hover_text(self.config.app_language.tooltip_hooks()).clicked() {
+                    self.hooks_buffer = self.config.hooks.get(&self.config.selected_group).map(Hooks::to_lines).unwrap_or_default();
+                    self.show_hooks_window = !self.show_hooks_window;
+                }
+                if ui.button("🗑").on_hover_text(self.config.app_language.tooltip_delete_group()).clicked() {
                     delete_group_clicked = true;
                 }
             });
 
-            if delete_group_clicked {
-                let group_name = self.selected_group.clone();
-                self.languages.remove(&group_name);
-                self.add_to_history(format!("Deleted Group: {}", group_name));
+            // Variable und Einfügeposition der Gruppe
+            let group = self.config.selected_group.clone();
+            let mut target_var = self.config.target_var(&group);
+            let mut position = self.config.insert_position(&group);
+            ui.horizontal(|ui| {
+                let lang = self.config.app_language;
+                ui.label(lang.label_target_var()).on_hover_text(lang.tooltip_target_var());
+                egui::ComboBox::from_id_salt("target_var")
+                    .selected_text(&target_var)
+                    .width(150.0)
+                    .show_ui(ui, |ui| {
+                        for var in logic::LIST_VARS {
+                            ui.selectable_value(&mut target_var, var.to_string(), var);
+                        }
+                    });
+                let response = ui.add(egui::TextEdit::singleline(&mut self.new_target_var)
+                    .hint_text(lang.hint_target_var())
+                    .desired_width(120.0));
+                if response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter)) {
+                    let name = self.new_target_var.trim();
+                    if !name.is_empty() && !name.contains('=') && !name.contains(char::is_whitespace) {
+                        target_var = name.to_string();
+                    }
+                    self.new_target_var.clear();
+                }
+
+                ui.add_space(10.0);
+                ui.label(lang.label_insert_position()).on_hover_text(lang.tooltip_insert_position());
+                let anchor = match &position {
+                    InsertPosition::Before(anchor) | InsertPosition::After(anchor) => anchor.clone(),
+                    _ => String::new(),
+                };
+                egui::ComboBox::from_id_salt("insert_position")
+                    .selected_text(lang.position_name(&position))
+                    .width(150.0)
+                    .show_ui(ui, |ui| {
+                        let options = [
+                            InsertPosition::Prepend,
+                            InsertPosition::Append,
+                            InsertPosition::Replace,
+                            InsertPosition::Before(anchor.clone()),
+                            InsertPosition::After(anchor),
+                        ];
+                        for option in options {
+                            let selected = std::mem::discriminant(&option) == std::mem::discriminant(&position);
+                            if ui.selectable_label(selected, lang.position_name(&option)).clicked() {
+                                position = option;
+                            }
+                        }
+                    });
 
-                if let Some(first_key) = self.languages.keys().next() {
-                    self.selected_group = first_key.clone();
+                if let InsertPosition::Before(anchor) | InsertPosition::After(anchor) = &mut position {
+                    // Die Ordner der Gruppe selbst taugen nicht als Anker, sie werden vorher entfernt
+                    let versions = self.config.languages.get(&group).map(Vec::as_slice).unwrap_or_default();
+                    let text = if anchor.is_empty() { lang.hint_anchor().to_string() } else { anchor.clone() };
+                    egui::ComboBox::from_id_salt("insert_anchor")
+                        .selected_text(text)
+                        .width(250.0)
+                        .show_ui(ui, |ui| {
+                            for e in self.effective_path.iter().filter(|e| e.layer == layers::PathLayer::User) {
+                                if !versions.iter().any(|v| v.has_dir(&e.entry)) {
+                                    ui.selectable_value(anchor, e.entry.clone(), &e.entry);
+                                }
+                            }
+                        });
+                }
+            });
+            if target_var != self.config.target_var(&group) {
+                self.config.set_target_var(&group, &target_var);
+                self.config.add_to_history(format!("Variable of {}: {}", group, self.config.target_var(&group)));
+                self.layers_key = None;
+            }
+            if position != self.config.insert_position(&group) {
+                self.config.add_to_history(format!("Insert position of {}: {}", group, position.describe()));
+                self.config.set_insert_position(&group, position);
+            }
+
+            // Profile
+            let mut activate_profile = false;
+            let mut delete_profile = false;
+            let mut save_profile = false;
+            ui.horizontal(|ui| {
+                let lang = self.config.app_language;
+                ui.label(lang.label_profile());
+                let selected_text = if self.matching_profiles.contains(&self.selected_profile) {
+                    format!("🟢 {}", self.selected_profile)
+                } else {
+                    self.selected_profile.clone()
+                };
+                egui::ComboBox::from_id_salt("profile_select")
+                    .selected_text(selected_text)
+                    .width(150.0)
+                    .show_ui(ui, |ui| {
+                        for (name, profile) in &self.config.profiles {
+                            let matches = self.matching_profiles.contains(name);
+                            let text = if matches { format!("🟢 {}", name) } else { name.clone() };
+                            let versions: Vec<String> = profile.iter().map(|(g, a)| format!("{} {}", g, a)).collect();
+                            let mut hover = versions.join("\n");
+                            if matches {
+                                hover = format!("{}\n\n{}", hover, lang.tooltip_profile_matches());
+                            }
+                            ui.selectable_value(&mut self.selected_profile, name.clone(), text).on_hover_text(hover);
+                        }
+                    });
+
+                if self.config.profiles.contains_key(&self.selected_profile) {
+                    activate_profile = ui.button("▶").on_hover_text(lang.tooltip_activate_profile()).clicked();
+                    delete_profile = ui.button("🗑").on_hover_text(lang.tooltip_delete_profile()).clicked();
+                }
+
+                ui.add_space(10.0);
+                let response = ui.add(egui::TextEdit::singleline(&mut self.new_profile_name).hint_text(lang.hint_profile_name()).desired_width(120.0));
+                let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                save_profile = ui.button("💾").on_hover_text(lang.tooltip_save_profile()).clicked() || submitted;
+            });
+
+            if activate_profile {
+                self.request_profile(self.selected_profile.clone());
+            }
+            if delete_profile {
+                let name = std::mem::take(&mut self.selected_profile);
+                self.config.profiles.remove(&name);
+                self.config.add_to_history(format!("Deleted profile: {}", name));
+            }
+            if save_profile {
+                self.save_profile();
+            }
+
+            if delete_group_clicked {
+                let group_name = self.config.selected_group.clone();
+                self.config.languages.remove(&group_name);
+                self.config.insert_positions.remove(&group_name);
+                self.config.target_vars.remove(&group_name);
+                self.config.hooks.remove(&group_name);
+                self.config.add_to_history(format!("Deleted Group: {}", group_name));
+
+                if let Some(first_key) = self.config.languages.keys().next() {
+                    self.config.selected_group = first_key.clone();
                 } else {
                     let general = "General".to_owned();
-                    self.languages.insert(general.clone(), Vec::new());
-                    self.selected_group = general;
+                    self.config.languages.insert(general.clone(), Vec::new());
+                    self.config.selected_group = general;
                 }
             }
 
@@ -395,33 +1375,37 @@ impl eframe::App for VersionSwitcherApp {
 
             // Neuer Eintrag
             ui.group(|ui| {
-                let accent = egui::Color32::from_rgb(self.accent_color[0], self.accent_color[1], self.accent_color[2]);
-                ui.label(egui::RichText::new(self.app_language.header_add_version(&self.selected_group)).strong().color(accent));
+                let accent = egui::Color32::from_rgb(self.config.accent_color[0], self.config.accent_color[1], self.config.accent_color[2]);
+                ui.label(egui::RichText::new(self.config.app_language.header_add_version(&self.config.selected_group)).strong().color(accent));
 
                 let mut add_clicked = false;
                 ui.horizontal(|ui| {
-                    ui.label(self.app_language.label_name());
-                    ui.add(egui::TextEdit::singleline(&mut self.new_alias_input).desired_width(80.0).hint_text(self.app_language.hint_name()));
-
-                    ui.label(self.app_language.label_path());
-                    let path_field = ui.add(egui::TextEdit::singleline(&mut self.new_path_input).desired_width(200.0).hint_text(self.app_language.hint_path()));
-
-                    if ui.button("📂").on_hover_text(self.app_language.tooltip_folder()).clicked() {
-                        if let Some(path) = rfd::FileDialog::new().pick_folder() {
-                            self.new_path_input = path.display().to_string();
-                            if self.new_alias_input.is_empty() {
-                                if let Some(folder_name) = path.file_name() {
-                                    self.new_alias_input = folder_name.to_string_lossy().to_string();
-                                }
-                            }
+                    ui.label(self.config.app_language.label_name());
+                    ui.add(egui::TextEdit::singleline(&mut self.new_alias_input).desired_width(80.0).hint_text(self.config.app_language.hint_name()));
+
+                    ui.label(self.config.app_language.label_path());
+                    let path_field = ui.add(egui::TextEdit::singleline(&mut self.new_path_input).desired_width(200.0).hint_text(self.config.app_language.hint_path()))
+                        .on_hover_text(self.config.app_language.tooltip_path_dirs(path_list::SEPARATOR));
+
+                    if ui.button("📂").on_hover_text(self.config.app_language.tooltip_folder()).clicked()
+                        && let Some(path) = rfd::FileDialog::new().pick_folder()
+                    {
+                        self.new_path_input = path.display().to_string();
+                        if self.new_alias_input.is_empty()
+                            && let Some(folder_name) = path.file_name()
+                        {
+                            self.new_alias_input = folder_name.to_string_lossy().to_string();
                         }
                     }
 
                     if !self.new_path_input.is_empty() {
-                        if Path::new(&self.new_path_input).is_dir() {
-                            ui.label("✅").on_hover_text(self.app_language.status_path_ok());
+                        let mut new_entry = VersionEntry::default();
+                        new_entry.set_dirs(&self.new_path_input);
+                        let missing = new_entry.missing_dirs();
+                        if missing.is_empty() {
+                            ui.label("✅").on_hover_text(self.config.app_language.status_path_ok());
                         } else {
-                            ui.label("❌").on_hover_text(self.app_language.status_path_missing());
+                            ui.label("❌").on_hover_text(format!("{}:\n{}", self.config.app_language.status_path_missing(), missing.join("\n")));
                         }
                     }
 
@@ -431,21 +1415,21 @@ impl eframe::App for VersionSwitcherApp {
                 });
 
                 ui.add_space(5.0);
-                if ui.add_sized([ui.available_width(), 25.0], egui::Button::new(format!("➕ {}", self.app_language.btn_add()))).clicked() {
+                if ui.add_sized([ui.available_width(), 25.0], egui::Button::new(format!("➕ {}", self.config.app_language.btn_add()))).clicked() {
                     add_clicked = true;
                 }
 
-                if add_clicked && !self.new_path_input.is_empty() {
-                    if let Some(versions) = self.languages.get_mut(&self.selected_group) {
-                        let alias_name = if self.new_alias_input.is_empty() { "Unbenannt".to_string() } else { self.new_alias_input.clone() };
-                        versions.push(VersionEntry {
-                            path: self.new_path_input.clone(),
-                            alias: alias_name.clone(),
-                        });
-                        self.add_to_history(format!("Added: {} -> {}", alias_name, self.selected_group));
-                        self.new_path_input.clear();
-                        self.new_alias_input.clear();
-                    }
+                if add_clicked
+                    && !self.new_path_input.is_empty()
+                    && let Some(versions) = self.config.languages.get_mut(&self.config.selected_group)
+                {
+                    let alias_name = if self.new_alias_input.is_empty() { "Unbenannt".to_string() } else { self.new_alias_input.clone() };
+                    let mut entry = VersionEntry { alias: alias_name.clone(), ..Default::default() };
+                    entry.set_dirs(&self.new_path_input);
+                    versions.push(entry);
+                    self.config.add_to_history(format!("Added: {} -> {}", alias_name, self.config.selected_group));
+                    self.new_path_input.clear();
+                    self.new_alias_input.clear();
                 }
             });
 
@@ -453,7 +1437,7 @@ impl eframe::App for VersionSwitcherApp {
 
             // --- HEADER LISTE & SUCHE (Repariert) ---
             ui.horizontal(|ui| {
-                ui.label(egui::RichText::new(self.app_language.header_available()).heading());
+                ui.label(egui::RichText::new(self.config.app_language.header_available()).heading());
 
                 // Wir nutzen `right_to_left` um die Elemente am rechten Rand auszurichten.
                 ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
@@ -462,7 +1446,7 @@ impl eframe::App for VersionSwitcherApp {
                     ui.horizontal(|ui| {
                         ui.label("🔍");
 
-                        let hint = match self.app_language {
+                        let hint = match self.config.app_language {
                             Language::English => "Search...",
                             Language::German => "Suchen...",
                         };
@@ -473,10 +1457,8 @@ impl eframe::App for VersionSwitcherApp {
                         });
 
                         // X-Button kommt DANACH, verändert also nicht den Index des Inputs
-                        if !self.search_query.is_empty() {
-                            if ui.button("❌").clicked() {
-                                self.search_query.clear();
-                            }
+                        if !self.search_query.is_empty() && ui.button("❌").clicked() {
+                            self.search_query.clear();
                         }
                     });
                 });
@@ -492,18 +1474,18 @@ impl eframe::App for VersionSwitcherApp {
 
             let mut log_message = None;
 
-            let lang = self.app_language;
+            let lang = self.config.app_language;
             let query = self.search_query.to_lowercase();
             let has_filter = !query.is_empty();
 
-            if let Some(versions) = self.languages.get_mut(&self.selected_group) {
+            if let Some(versions) = self.config.languages.get_mut(&self.config.selected_group) {
                 egui::ScrollArea::vertical().show(ui, |ui| {
                     let versions_len = versions.len();
 
                     for (idx, entry) in versions.iter_mut().enumerate() {
                         if has_filter {
                             let matches_alias = entry.alias.to_lowercase().contains(&query);
-                            let matches_path = entry.path.to_lowercase().contains(&query);
+                            let matches_path = entry.dirs().any(|d| d.to_lowercase().contains(&query));
                             if !matches_alias && !matches_path {
                                 continue;
                             }
@@ -515,7 +1497,8 @@ impl eframe::App for VersionSwitcherApp {
                                     ui.label("Name:");
                                     ui.text_edit_singleline(&mut self.edit_name_buffer);
                                     ui.label("Pfad:");
-                                    ui.text_edit_singleline(&mut self.edit_path_buffer);
+                                    ui.text_edit_singleline(&mut self.edit_path_buffer)
+                                        .on_hover_text(lang.tooltip_path_dirs(path_list::SEPARATOR));
 
                                     if ui.button("💾").on_hover_text(lang.tooltip_save()).clicked() {
                                         save_edit = Some(idx);
@@ -524,45 +1507,110 @@ impl eframe::App for VersionSwitcherApp {
                                         cancel_edit = true;
                                     }
                                 });
+                                ui.label(lang.label_env_vars());
+                                ui.add(egui::TextEdit::multiline(&mut self.edit_env_buffer)
+                                    .hint_text("JAVA_HOME=${path}")
+                                    .desired_rows(2)
+                                    .desired_width(f32::INFINITY));
+                                ui.label(lang.label_lists()).on_hover_text(lang.tooltip_lists(path_list::SEPARATOR));
+                                ui.add(egui::TextEdit::multiline(&mut self.edit_lists_buffer)
+                                    .hint_text("PYTHONPATH=…")
+                                    .desired_rows(2)
+                                    .desired_width(f32::INFINITY));
+                                ui.label(lang.label_hooks()).on_hover_text(lang.tooltip_hook_lines());
+                                ui.add(egui::TextEdit::multiline(&mut self.edit_hooks_buffer)
+                                    .hint_text("post: npm config set prefix ${path}")
+                                    .desired_rows(2)
+                                    .desired_width(f32::INFINITY));
                             } else {
                                 ui.horizontal(|ui| {
-                                    let is_active = current_sys_paths.iter().any(|p| p.eq_ignore_ascii_case(&entry.path));
-
-                                    if is_active { ui.label("🟢"); } else { ui.label("⚪"); }
+                                    // Aktiv heißt im PATH-Modus: alle Ordner der Version stehen in der Variable der Gruppe
+                                    let (present, missing): (Vec<&str>, Vec<&str>) = entry.list_dirs(&group_target.var, &group_target.var)
+                                        .into_iter()
+                                        .partition(|d| group_list.contains(d));
+                                    let is_active = match group_mode {
+                                        SwitchMode::Path => missing.is_empty(),
+                                        SwitchMode::Shims => shim_target.as_deref().is_some_and(|t| path_list::same_entry(t, &entry.path)),
+                                    };
+
+                                    if is_active {
+                                        ui.label("🟢");
+                                    } else if group_mode == SwitchMode::Path && !present.is_empty() {
+                                        ui.label("🟡").on_hover_text(lang.tooltip_partly_active(&missing.join("\n")));
+                                    } else {
+                                        ui.label("⚪");
+                                    }
+                                    if let Some(shadows) = self.shadows.get(&entry.path).filter(|_| is_active) {
+                                        let details: Vec<String> = shadows.iter()
+                                            .map(|s| format!("{} ← {} [{}]", s.name, s.by.entry, lang.layer_name(s.by.layer)))
+                                            .collect();
+                                        ui.colored_label(egui::Color32::from_rgb(255, 165, 0), "⚠")
+                                            .on_hover_text(lang.tooltip_shadowed(&details.join("\n")));
+                                    }
 
                                     if !has_filter {
                                         ui.vertical(|ui| {
-                                            if idx > 0 {
-                                                if ui.small_button("⬆").on_hover_text(lang.tooltip_move_up()).clicked() { move_up = Some(idx); }
-                                            }
-                                            if idx < versions_len - 1 {
-                                                if ui.small_button("⬇").on_hover_text(lang.tooltip_move_down()).clicked() { move_down = Some(idx); }
-                                            }
+                                            if idx > 0 && ui.small_button("⬆").on_hover_text(lang.tooltip_move_up()).clicked() { move_up = Some(idx); }
+                                            if idx < versions_len - 1 && ui.small_button("⬇").on_hover_text(lang.tooltip_move_down()).clicked() { move_down = Some(idx); }
                                         });
                                     }
 
                                     ui.vertical(|ui| {
-                                        ui.label(egui::RichText::new(&entry.alias).strong().size(16.0));
-                                        let path_exists = Path::new(&entry.path).is_dir();
-                                        let path_text = egui::RichText::new(&entry.path).small().weak();
-                                        if !path_exists {
-                                            ui.horizontal(|ui| {
-                                                ui.label(path_text.color(egui::Color32::RED));
-                                                ui.label("⚠️").on_hover_text(lang.tooltip_missing_folder());
-                                            });
-                                        } else {
-                                            ui.label(path_text);
+                                        ui.horizontal(|ui| {
+                                            ui.label(egui::RichText::new(&entry.alias).strong().size(16.0));
+                                            if self.probe_pending.contains(&entry.path) {
+                                                ui.label(egui::RichText::new(lang.label_probing()).small().weak());
+                                            } else if let Some(result) = self.config.probe_cache.get(&entry.path) {
+                                                match (&result.version, &result.error) {
+                                                    (Some(found), _) if probe::is_mismatch(&entry.alias, found) => {
+                                                        ui.colored_label(egui::Color32::YELLOW, format!("⚠ {}", found))
+                                                            .on_hover_text(lang.tooltip_probe_mismatch(found));
+                                                    }
+                                                    (Some(found), _) => {
+                                                        ui.label(egui::RichText::new(found).small().weak());
+                                                    }
+                                                    (None, Some(e)) => {
+                                                        ui.label(egui::RichText::new("?").small().weak()).on_hover_text(e);
+                                                    }
+                                                    (None, None) => {}
+                                                }
+                                            }
+                                        });
+                                        let missing_dirs = entry.missing_dirs();
+                                        for dir in entry.dirs() {
+                                            let path_text = egui::RichText::new(dir).small().weak();
+                                            if missing_dirs.contains(&dir) {
+                                                ui.horizontal(|ui| {
+                                                    ui.label(path_text.color(egui::Color32::RED));
+                                                    ui.label("⚠️").on_hover_text(lang.tooltip_missing_folder());
+                                                });
+                                            } else {
+                                                ui.label(path_text);
+                                            }
+                                        }
+                                        if !entry.env.is_empty() {
+                                            let names: Vec<&str> = entry.env.keys().map(|k| k.as_str()).collect();
+                                            ui.label(egui::RichText::new(names.join(", ")).small().weak())
+                                                .on_hover_text(env_vars::format_lines(&entry.env));
+                                        }
+                                        if !entry.lists.is_empty() {
+                                            let names: Vec<String> = entry.lists.keys().map(|k| format!("+{}", k)).collect();
+                                            let mut text = egui::RichText::new(names.join(", ")).small().weak();
+                                            if entry.lists.values().flatten().any(|d| missing_dirs.contains(&d.as_str())) {
+                                                text = text.color(egui::Color32::RED);
+                                            }
+                                            ui.label(text).on_hover_text(entry.lists_string());
                                         }
                                     });
 
                                     ui.with_layout(egui::Layout::right_to_left(egui::Align::Center), |ui| {
                                         if ui.button("🗑").on_hover_text(lang.tooltip_delete()).clicked() { delete_index = Some(idx); }
-                                        if ui.button("✏").on_hover_text(lang.tooltip_edit()).clicked() { start_edit = Some((idx, entry.alias.clone(), entry.path.clone())); }
+                                        if ui.button("✏").on_hover_text(lang.tooltip_edit()).clicked() { start_edit = Some(idx); }
 
                                         let btn_text = if is_active { lang.btn_is_active() } else { lang.btn_activate() };
                                         let btn = egui::Button::new(btn_text).selected(is_active);
-                                        if ui.add_enabled(!is_active, btn).clicked() {
-                                            activate_version = Some((entry.path.clone(), entry.alias.clone()));
+                                        if ui.add_enabled(!is_active && self.pending_action.is_none(), btn).clicked() {
+                                            activate_version = Some(entry.clone());
                                         }
                                     });
                                 });
@@ -574,17 +1622,25 @@ impl eframe::App for VersionSwitcherApp {
                 if let Some(idx) = move_up { versions.swap(idx, idx - 1); }
                 if let Some(idx) = move_down { versions.swap(idx, idx + 1); }
 
-                if let Some((idx, name, path)) = start_edit {
+                if let Some(idx) = start_edit
+                    && let Some(entry) = versions.get(idx)
+                {
                     self.editing_index = Some(idx);
-                    self.edit_name_buffer = name;
-                    self.edit_path_buffer = path;
+                    self.edit_name_buffer = entry.alias.clone();
+                    self.edit_path_buffer = entry.dirs_string();
+                    self.edit_env_buffer = env_vars::format_lines(&entry.env);
+                    self.edit_lists_buffer = entry.lists_string();
+                    self.edit_hooks_buffer = entry.hooks.to_lines();
                 }
 
                 if let Some(idx) = save_edit {
                     if let Some(entry) = versions.get_mut(idx) {
                         log_message = Some(format!("Edited: {} -> {}", entry.alias, self.edit_name_buffer));
                         entry.alias = self.edit_name_buffer.clone();
-                        entry.path = self.edit_path_buffer.clone();
+                        entry.set_dirs(&self.edit_path_buffer);
+                        entry.env = env_vars::parse_lines(&self.edit_env_buffer);
+                        entry.set_lists(&self.edit_lists_buffer);
+                        entry.hooks = Hooks::parse_lines(&self.edit_hooks_buffer);
                     }
                     self.editing_index = None;
                 }
@@ -601,26 +1657,112 @@ impl eframe::App for VersionSwitcherApp {
             }
 
             if let Some(msg) = log_message {
-                self.add_to_history(msg);
+                self.config.add_to_history(msg);
             }
 
-            if let Some((path, alias)) = activate_version {
-                self.switch_version(&path, &alias);
+            if let Some(entry) = activate_version {
+                self.request_switch(entry);
             }
 
             ui.add_space(10.0);
             ui.separator();
             ui.label(format!("Status: {}",
                              if self.status_message == "Bereit." || self.status_message == "Ready." {
-                                 self.app_language.status_ready().to_string()
+                                 self.config.app_language.status_ready().to_string()
                              } else {
                                  self.status_message.clone()
                              }
             ));
 
-            ui.collapsing("System PATH (Debug)", |ui| {
-                ui.monospace(current_sys_path_str);
+            let mut use_shims = self.config.switch_mode == SwitchMode::Shims;
+            if ui.checkbox(&mut use_shims, self.config.app_language.label_shim_mode())
+                .on_hover_text(self.config.app_language.tooltip_shim_mode())
+                .changed()
+            {
+                self.config.switch_mode = if use_shims { SwitchMode::Shims } else { SwitchMode::Path };
+            }
+            ui.checkbox(&mut self.config.confirm_changes, self.config.app_language.label_confirm_changes())
+                .on_hover_text(self.config.app_language.tooltip_confirm_changes());
+            ui.horizontal(|ui| {
+                ui.label(self.config.app_language.label_length_warning());
+                ui.add(egui::DragValue::new(&mut self.config.path_length_warning).range(256..=logic::PATH_HARD_LIMIT));
+            });
+
+            let path_length = logic::path_length(&current_sys_path_str);
+            if path_length > self.config.path_length_warning {
+                ui.horizontal(|ui| {
+                    ui.colored_label(egui::Color32::from_rgb(255, 165, 0), self.config.app_language.label_path_length(path_length, self.config.path_length_warning));
+                    if ui.button(self.config.app_language.btn_compact()).on_hover_text(self.config.app_language.tooltip_compact()).clicked() {
+                        self.request_compact();
+                    }
+                });
+            }
+
+            let mut search_which = false;
+            ui.collapsing(self.config.app_language.header_effective_path(), |ui| {
+                if ui.small_button("🔄").clicked() {
+                    self.layers_key = None;
+                }
+                egui::ScrollArea::vertical().id_salt("effective_path").max_height(200.0).show(ui, |ui| {
+                    for e in &self.effective_path {
+                        let layer = self.config.app_language.layer_name(e.layer);
+                        let text = egui::RichText::new(format!("[{:<8}] {}", layer, e.entry)).monospace();
+                        match e.layer {
+                            layers::PathLayer::User => ui.label(text.strong()),
+                            _ => ui.label(text.weak()),
+                        };
+                    }
+                });
+
+                ui.separator();
+                ui.horizontal(|ui| {
+                    ui.label(self.config.app_language.label_which());
+                    let response = ui.add(egui::TextEdit::singleline(&mut self.which_query).hint_text("python").desired_width(150.0));
+                    let submitted = response.lost_focus() && ui.input(|i| i.key_pressed(egui::Key::Enter));
+                    if submitted || ui.button("🔍").clicked() {
+                        search_which = true;
+                    }
+                });
+                match &self.which_results {
+                    Some(results) if results.is_empty() => {
+                        ui.colored_label(egui::Color32::RED, self.config.app_language.label_which_none());
+                    }
+                    Some(results) => {
+                        for (i, m) in results.iter().enumerate() {
+                            let layer = self.config.app_language.layer_name(m.source.layer);
+                            let owner = match &m.owner {
+                                Some(o) if o.via_shim => format!("  ({} {}, Shim)", o.group, o.alias),
+                                Some(o) => format!("  ({} {})", o.group, o.alias),
+                                None => String::new(),
+                            };
+                            let text = egui::RichText::new(format!("[{:<8}] {}{}", layer, m.file.display(), owner)).monospace();
+                            // Nur der erste Treffer wird tatsächlich gestartet
+                            if i == 0 {
+                                ui.colored_label(egui::Color32::GREEN, text.strong())
+                                    .on_hover_text(self.config.app_language.tooltip_which_winner());
+                            } else {
+                                ui.label(text.weak());
+                            }
+                        }
+                    }
+                    None => {}
+                }
             });
+            if search_which {
+                self.run_which();
+            }
         });
     }
-}
\ No newline at end of file
+}
+
+/// Zeigt einen PATH-Vergleich als farbige Liste.
+fn show_changes(ui: &mut egui::Ui, changes: &[PathChange]) {
+    for change in changes {
+        match change {
+            PathChange::Kept(p) => ui.label(egui::RichText::new(format!("  {}", p)).monospace().weak()),
+            PathChange::Added(p) => ui.colored_label(egui::Color32::GREEN, egui::RichText::new(format!("+ {}", p)).monospace()),
+            PathChange::Removed(p) => ui.colored_label(egui::Color32::RED, egui::RichText::new(format!("- {}", p)).monospace()),
+            PathChange::Moved(p) => ui.colored_label(egui::Color32::LIGHT_BLUE, egui::RichText::new(format!("~ {}", p)).monospace()),
+        };
+    }
+}
@@ -0,0 +1,347 @@
+use chrono::Local;
+use serde::Serialize;
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+use crate::backend::EnvBackend;
+use crate::layers::{self, LayeredEntry, PathLayer};
+use crate::logic;
+use crate::path_list;
+
+/// Wie ernst ein Fund ist. `Critical` ist größer als `Warning`.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Critical,
+}
+
+/// Warum ein Eintrag riskant ist.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub enum AuditKind {
+    /// Relativer Eintrag wie `.` oder `bin`, hängt vom aktuellen Verzeichnis ab.
+    Relative,
+    /// Leerer Eintrag (`::` oder `:` am Ende), wirkt unter Unix wie `.`.
+    Empty,
+    /// Andere Benutzer können hier Programme ablegen, und der Ordner steht vor `system_dir`.
+    WritableBeforeSystem { system_dir: String },
+    /// Liegt auf einem Wechseldatenträger.
+    RemovableDrive,
+    /// Liegt auf einem Netzlaufwerk.
+    NetworkDrive,
+    /// Der Ordner fehlt, andere Benutzer könnten ihn aber in `parent` anlegen.
+    PlantableMissing { parent: String },
+}
+
+/// Ein riskanter Eintrag im wirksamen PATH.
+#[derive(Serialize, Clone, Debug)]
+pub struct AuditFinding {
+    pub severity: Severity,
+    pub layer: PathLayer,
+    pub entry: String,
+    pub kind: AuditKind,
+    /// Die Schreibrechte wurden nur anhand des Orts geschätzt, nicht aus den Rechten gelesen.
+    pub guessed: bool,
+}
+
+impl AuditFinding {
+    pub fn describe(&self) -> String {
+        let text = match &self.kind {
+            AuditKind::Relative => "Relative entry, resolved against the current directory".to_string(),
+            AuditKind::Empty => "Empty entry, acts like the current directory".to_string(),
+            AuditKind::WritableBeforeSystem { system_dir } => {
+                format!("Writable by other users and searched before {}", system_dir)
+            }
+            AuditKind::RemovableDrive => "On a removable drive".to_string(),
+            AuditKind::NetworkDrive => "On a network drive".to_string(),
+            AuditKind::PlantableMissing { parent } => {
+                format!("Does not exist, but other users can create it in {}", parent)
+            }
+        };
+        if self.guessed {
+            format!("{} (guessed from the location, permissions not checked)", text)
+        } else {
+            text
+        }
+    }
+}
+
+/// Bericht für die Ablage, z.B. für ein Security-Review.
+#[derive(Serialize)]
+struct AuditReport<'a> {
+    time: String,
+    findings: Vec<ReportLine<'a>>,
+}
+
+#[derive(Serialize)]
+struct ReportLine<'a> {
+    #[serde(flatten)]
+    finding: &'a AuditFinding,
+    message: String,
+}
+
+/// Prüft den wirksamen PATH aus den drei Ebenen. Kritische Funde stehen vorne,
+/// innerhalb einer Stufe bleibt die Reihenfolge im PATH erhalten.
+pub fn audit(machine: &str, user: &str, session: &str) -> Vec<AuditFinding> {
+    let mut findings = Vec::new();
+
+    // Leere Einträge verschwinden beim Zerlegen, daher in den Rohwerten suchen
+    if !cfg!(windows) {
+        for (layer, value) in [(PathLayer::Machine, machine), (PathLayer::User, user), (PathLayer::Session, session)] {
+            if !value.is_empty() && value.split(path_list::SEPARATOR).any(|p| p.trim().is_empty()) {
+                findings.push(AuditFinding { severity: Severity::Critical, layer, entry: String::new(), kind: AuditKind::Empty, guessed: false });
+            }
+        }
+    }
+
+    let effective = layers::effective_path(machine, user, session);
+    for (idx, e) in effective.iter().enumerate() {
+        for (severity, kind) in check_entry(e, &effective[idx + 1..]) {
+            let guessed = WRITE_ACCESS_GUESSED
+                && matches!(kind, AuditKind::WritableBeforeSystem { .. } | AuditKind::PlantableMissing { .. });
+            findings.push(AuditFinding { severity, layer: e.layer, entry: e.entry.clone(), kind, guessed });
+        }
+    }
+
+    findings.sort_by_key(|f| std::cmp::Reverse(f.severity));
+    findings
+}
+
+/// Prüft den PATH, den neue Programme bekommen, mit dem Benutzer-PATH aus dem Backend.
+pub fn audit_current(backend: &dyn EnvBackend) -> Vec<AuditFinding> {
+    audit(&layers::machine_path(), &logic::get_current_path_var(backend), &layers::session_path())
+}
+
+fn check_entry(e: &LayeredEntry, later: &[LayeredEntry]) -> Vec<(Severity, AuditKind)> {
+    let dir = PathBuf::from(path_list::expand(&e.entry));
+    if !dir.is_absolute() {
+        return vec![(Severity::Critical, AuditKind::Relative)];
+    }
+
+    let mut result = Vec::new();
+    if let Some(kind) = drive_kind(&dir) {
+        result.push((Severity::Warning, kind));
+    }
+
+    if dir.is_dir() {
+        let system_dir = later.iter().find(|l| is_system_dir(&l.entry));
+        if let (Some(severity), Some(system_dir)) = (others_can_write(&dir), system_dir) {
+            result.push((severity, AuditKind::WritableBeforeSystem { system_dir: system_dir.entry.clone() }));
+        }
+    } else if !dir.exists()
+        && let Some(parent) = dir.ancestors().skip(1).find(|p| p.is_dir())
+        && let Some(severity) = others_can_write(parent)
+    {
+        result.push((severity, AuditKind::PlantableMissing { parent: parent.display().to_string() }));
+    }
+    result
+}
+
+/// Ordner des Betriebssystems, deren Programme nicht verdeckt werden sollten.
+fn is_system_dir(entry: &str) -> bool {
+    let normalized = path_list::normalize(entry);
+    if cfg!(windows) {
+        let root = path_list::normalize("%SystemRoot%");
+        normalized == root || normalized.starts_with(&format!("{}\\", root))
+    } else {
+        matches!(normalized.as_str(), "/bin" | "/sbin" | "/usr/bin" | "/usr/sbin" | "/usr/local/bin" | "/usr/local/sbin")
+    }
+}
+
+/// Unter Windows werden keine ACLs gelesen, `others_can_write` schätzt dort nur.
+const WRITE_ACCESS_GUESSED: bool = cfg!(windows);
+
+/// Ob andere Benutzer in `dir` Dateien anlegen können.
+///
+/// Unter Unix zählen die Rechte-Bits: für alle beschreibbar ist kritisch, für eine fremde Gruppe
+/// eine Warnung. Unter Windows ist das eine Abschätzung ohne ACLs: Ordner außerhalb von Windows,
+/// Programme und Benutzerprofil erben auf dem Systemlaufwerk Schreibrechte für alle Benutzer.
+#[cfg(not(windows))]
+fn others_can_write(dir: &Path) -> Option<Severity> {
+    use std::os::unix::fs::MetadataExt;
+
+    // Die eigene Gruppe (wie die des Home-Ordners) zählt nicht als fremd
+    let own_gid = std::env::var_os("HOME").and_then(|h| Path::new(&h).metadata().ok()).map(|m| m.gid());
+    writable_for_others(dir, own_gid)
+}
+
+#[cfg(not(windows))]
+fn writable_for_others(dir: &Path, own_gid: Option<u32>) -> Option<Severity> {
+    use std::os::unix::fs::{MetadataExt, PermissionsExt};
+
+    let meta = dir.metadata().ok()?;
+    let mode = meta.permissions().mode();
+    if mode & 0o002 != 0 {
+        return Some(Severity::Critical);
+    }
+    if mode & 0o020 != 0 && own_gid != Some(meta.gid()) {
+        return Some(Severity::Warning);
+    }
+    None
+}
+
+#[cfg(windows)]
+fn others_can_write(dir: &Path) -> Option<Severity> {
+    let normalized = path_list::normalize(&dir.display().to_string());
+    let protected = ["%SystemRoot%", "%ProgramFiles%", "%ProgramFiles(x86)%", "%ProgramW6432%", "%USERPROFILE%"];
+    let is_protected = protected.iter()
+        .map(|var| path_list::normalize(var))
+        .filter(|root| !root.contains('%'))
+        .any(|root| normalized == root || normalized.starts_with(&format!("{}\\", root)));
+    let on_system_drive = std::env::var("SystemDrive")
+        .is_ok_and(|drive| normalized.starts_with(&drive.to_lowercase()));
+
+    (on_system_drive && !is_protected).then_some(Severity::Warning)
+}
+
+/// Ob der Ordner auf einem Wechseldatenträger oder Netzlaufwerk liegt.
+#[cfg(windows)]
+fn drive_kind(dir: &Path) -> Option<AuditKind> {
+    use std::os::windows::ffi::OsStrExt;
+
+    let text = dir.display().to_string();
+    if text.starts_with(r"\\") {
+        return Some(AuditKind::NetworkDrive);
+    }
+    let root: Vec<u16> = std::ffi::OsStr::new(text.get(..3)?).encode_wide().chain(Some(0)).collect();
+    match unsafe { GetDriveTypeW(root.as_ptr()) } {
+        DRIVE_REMOVABLE | DRIVE_CDROM => Some(AuditKind::RemovableDrive),
+        DRIVE_REMOTE => Some(AuditKind::NetworkDrive),
+        _ => None,
+    }
+}
+
+#[cfg(not(windows))]
+fn drive_kind(dir: &Path) -> Option<AuditKind> {
+    const NETWORK_FS: [&str; 10] = ["nfs", "nfs4", "cifs", "smbfs", "smb3", "fuse.sshfs", "9p", "afs", "ceph", "glusterfs"];
+
+    let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+    // Längster Einhängepunkt, unter dem der Ordner liegt
+    let mounts = std::fs::read_to_string("/proc/mounts").unwrap_or_default();
+    let mount = mounts.lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let point = fields.nth(1)?.replace("\\040", " ");
+            let fs_type = fields.next()?.to_string();
+            Some((point, fs_type))
+        })
+        .filter(|(point, _)| dir.starts_with(point))
+        .max_by_key(|(point, _)| point.len());
+
+    if let Some((point, fs_type)) = mount {
+        if NETWORK_FS.contains(&fs_type.as_str()) {
+            return Some(AuditKind::NetworkDrive);
+        }
+        if point.starts_with("/media/") || point.starts_with("/run/media/") {
+            return Some(AuditKind::RemovableDrive);
+        }
+    }
+    // macOS hängt externe Laufwerke unter /Volumes ein
+    if cfg!(target_os = "macos") && dir.starts_with("/Volumes") {
+        return Some(AuditKind::RemovableDrive);
+    }
+    None
+}
+
+// Aus kernel32, wie in `backend.rs` ohne winapi deklariert.
+#[cfg(windows)]
+const DRIVE_REMOVABLE: u32 = 2;
+#[cfg(windows)]
+const DRIVE_REMOTE: u32 = 4;
+#[cfg(windows)]
+const DRIVE_CDROM: u32 = 5;
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+unsafe extern "system" {
+    fn GetDriveTypeW(root: *const u16) -> u32;
+}
+
+/// Schreibt die Funde als JSON-Bericht nach `path`.
+pub fn export_to_path(findings: &[AuditFinding], path: &Path) -> Result<(), String> {
+    let report = AuditReport {
+        time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        findings: findings.iter().map(|f| ReportLine { finding: f, message: f.describe() }).collect(),
+    };
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(BufWriter::new(file), &report).map_err(|e| e.to_string())
+}
+
+/// Fragt per Dialog nach einer Datei und speichert den Bericht dort.
+#[cfg(feature = "gui")]
+pub fn export_to_file(findings: &[AuditFinding]) -> Result<(), String> {
+    if let Some(path) = rfd::FileDialog::new().set_file_name("path_audit.json").save_file() {
+        return export_to_path(findings, &path);
+    }
+    Err("Cancelled".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    fn dir_with_mode(parent: &Path, mode: u32) -> PathBuf {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = parent.join(format!("bin{:o}", mode));
+        std::fs::create_dir(&dir).unwrap();
+        std::fs::set_permissions(&dir, std::fs::Permissions::from_mode(mode)).unwrap();
+        dir
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn world_writable_dir_before_system_dir_is_critical() {
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = dir_with_mode(tmp.path(), 0o777);
+        let user = format!("{}:/usr/bin", dir.display());
+
+        let findings = audit("", &user, "");
+
+        let finding = findings.iter().find(|f| f.entry == dir.display().to_string()).unwrap();
+        assert_eq!(finding.severity, Severity::Critical);
+        assert_eq!(finding.kind, AuditKind::WritableBeforeSystem { system_dir: "/usr/bin".to_string() });
+        assert!(!finding.guessed);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn group_writable_dir_warns_only_for_a_foreign_group() {
+        use std::os::unix::fs::MetadataExt;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let dir = dir_with_mode(tmp.path(), 0o775);
+        let gid = dir.metadata().unwrap().gid();
+
+        assert_eq!(writable_for_others(&dir, Some(gid.wrapping_add(1))), Some(Severity::Warning));
+        assert_eq!(writable_for_others(&dir, Some(gid)), None);
+        assert_eq!(writable_for_others(&dir_with_mode(tmp.path(), 0o755), None), None);
+    }
+
+    #[test]
+    fn report_lists_findings_with_message() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("report.json");
+        let findings = [AuditFinding {
+            severity: Severity::Critical,
+            layer: PathLayer::User,
+            entry: "bin".to_string(),
+            kind: AuditKind::Relative,
+            guessed: false,
+        }];
+
+        export_to_path(&findings, &path).unwrap();
+
+        let report: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+        assert!(report["time"].is_string());
+        assert_eq!(report["findings"], serde_json::json!([{
+            "severity": "Critical",
+            "layer": "User",
+            "entry": "bin",
+            "kind": "Relative",
+            "guessed": false,
+            "message": findings[0].describe(),
+        }]));
+    }
+}
@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+/// Abstraktion über den Ort, an dem Benutzer-Umgebungsvariablen gespeichert werden.
+///
+/// Unter Windows ist das die Registry (`HKEY_CURRENT_USER\Environment`),
+/// unter Linux/Unix die Shell-Startdateien (siehe `shell_profile`),
+/// in Tests ein Speicher im RAM oder eine Datei.
+pub trait EnvBackend {
+    /// Liest den Wert einer Variable. `None`, wenn sie nicht existiert.
+    fn read_var(&self, name: &str) -> Option<String>;
+
+    /// Schreibt (oder erstellt) eine Variable.
+    fn write_var(&self, name: &str, value: &str) -> Result<(), String>;
+
+    /// Löscht eine Variable. Existiert sie nicht, ist das kein Fehler.
+    fn remove_var(&self, name: &str) -> Result<(), String>;
+
+    /// Namen aller Variablen des Benutzers.
+    fn var_names(&self) -> Vec<String>;
+
+    /// Ob Verweise im Wert (`%VAR%` bzw. `$VAR`) beim Lesen durch das System aufgelöst werden.
+    /// Backends ohne eigene Art leiten das aus dem Wert ab.
+    fn is_expandable(&self, name: &str) -> bool {
+        self.read_var(name).is_some_and(|v| has_references(&v))
+    }
+
+    /// Schreibt eine Variable ausdrücklich als erweiterbaren oder wörtlichen Wert.
+    fn write_var_as(&self, name: &str, value: &str, _expandable: bool) -> Result<(), String> {
+        self.write_var(name, value)
+    }
+
+    /// Der Wert, den `read_var` nach `write_var(name, value)` liefern würde. Schreibt ein
+    /// Backend Werte um, gilt ein unverändert geplanter Wert so nicht als Änderung.
+    fn stored_value(&self, _name: &str, value: &str) -> String {
+        value.to_string()
+    }
+
+    /// Teilt dem System mit, dass sich die Umgebung geändert hat.
+    fn broadcast_change(&self);
+
+    /// Ordner für Snapshots und Sicherungen dieses Backends. `None`: Es wird nichts gesichert.
+    fn data_dir(&self) -> Option<PathBuf> {
+        crate::config::data_dir()
+    }
+}
+
+/// Ob ein Wert Verweise auf andere Variablen enthält, die das System auflösen würde.
+pub fn has_references(value: &str) -> bool {
+    if cfg!(windows) { value.contains('%') } else { value.contains('$') || value.starts_with('~') }
+}
+
+// --- WINDOWS REGISTRY ---
+
+#[cfg(windows)]
+const ENV_KEY: &str = "Environment";
+
+/// Schreibt direkt nach `HKEY_CURRENT_USER\Environment`.
+#[cfg(windows)]
+pub struct RegistryBackend;
+
+#[cfg(windows)]
+impl EnvBackend for RegistryBackend {
+    fn read_var(&self, name: &str) -> Option<String> {
+        use winreg::enums::*;
+        use winreg::RegKey;
+
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let env = hkcu.open_subkey(ENV_KEY).or_else(|_| hkcu.create_subkey(ENV_KEY).map(|(key, _)| key)).ok()?;
+        env.get_value(name).ok()
+    }
+
+    fn write_var(&self, name: &str, value: &str) -> Result<(), String> {
+        use winreg::enums::*;
+        use winreg::RegKey;
+
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let env = hkcu.open_subkey_with_flags(ENV_KEY, KEY_READ | KEY_WRITE)
+            .map_err(|e| format!("Registry Error: {}", e))?;
+
+        // Typ beibehalten: Ein REG_EXPAND_SZ als REG_SZ zu schreiben, macht aus
+        // `%USERPROFILE%\bin` einen Ordner, den es so nicht gibt.
+        let was_expandable = env.get_raw_value(name).is_ok_and(|v| v.vtype == REG_EXPAND_SZ);
+        self.write_var_as(name, value, was_expandable || value.contains('%'))
+    }
+
+    fn var_names(&self) -> Vec<String> {
+        use winreg::enums::*;
+        use winreg::RegKey;
+
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let Ok(env) = hkcu.open_subkey(ENV_KEY) else { return Vec::new() };
+        env.enum_values().filter_map(|v| v.ok()).map(|(name, _)| name).collect()
+    }
+
+    fn is_expandable(&self, name: &str) -> bool {
+        use winreg::enums::*;
+        use winreg::RegKey;
+
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        hkcu.open_subkey(ENV_KEY)
+            .and_then(|env| env.get_raw_value(name))
+            .is_ok_and(|v| v.vtype == REG_EXPAND_SZ)
+    }
+
+    fn write_var_as(&self, name: &str, value: &str, expandable: bool) -> Result<(), String> {
+        use winreg::enums::*;
+        use winreg::{RegKey, RegValue};
+
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let env = hkcu.open_subkey_with_flags(ENV_KEY, KEY_READ | KEY_WRITE)
+            .map_err(|e| format!("Registry Error: {}", e))?;
+        let vtype = if expandable { REG_EXPAND_SZ } else { REG_SZ };
+        let bytes = value.encode_utf16().chain(Some(0)).flat_map(|c| c.to_le_bytes()).collect();
+        env.set_raw_value(name, &RegValue { bytes, vtype })
+            .map_err(|e| format!("Write Error: {}", e))
+    }
+
+    fn remove_var(&self, name: &str) -> Result<(), String> {
+        use winreg::enums::*;
+        use winreg::RegKey;
+
+        let hkcu = RegKey::predef(HKEY_CURRENT_USER);
+        let env = hkcu.open_subkey_with_flags(ENV_KEY, KEY_WRITE)
+            .map_err(|e| format!("Registry Error: {}", e))?;
+        match env.delete_value(name) {
+            Ok(_) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(format!("Write Error: {}", e)),
+        }
+    }
+
+    fn broadcast_change(&self) {
+        // Windows benachrichtigen (Broadcast)
+        let lp_param = std::ffi::CString::new("Environment").unwrap();
+        unsafe {
+            SendMessageTimeoutA(
+                HWND_BROADCAST, WM_SETTINGCHANGE, 0, lp_param.as_ptr() as isize,
+                SMTO_ABORTIFHUNG, 5000, std::ptr::null_mut(),
+            );
+        }
+    }
+}
+
+// Die paar Funktionen aus user32 deklarieren wir selbst, damit die CLI ohne winapi auskommt.
+#[cfg(windows)]
+const HWND_BROADCAST: isize = 0xffff;
+#[cfg(windows)]
+const WM_SETTINGCHANGE: u32 = 0x001A;
+#[cfg(windows)]
+const SMTO_ABORTIFHUNG: u32 = 0x0002;
+
+#[cfg(windows)]
+#[link(name = "user32")]
+unsafe extern "system" {
+    fn SendMessageTimeoutA(
+        hwnd: isize, msg: u32, wparam: usize, lparam: isize,
+        flags: u32, timeout: u32, result: *mut usize,
+    ) -> isize;
+}
+
+// --- IN-MEMORY ---
+
+/// Hält alle Variablen nur im Speicher. Praktisch für Tests und Trockenläufe.
+#[derive(Default)]
+pub struct MemoryBackend {
+    vars: Mutex<HashMap<String, String>>,
+}
+
+impl MemoryBackend {
+    pub fn with_vars(vars: HashMap<String, String>) -> Self {
+        Self { vars: Mutex::new(vars) }
+    }
+}
+
+impl EnvBackend for MemoryBackend {
+    fn read_var(&self, name: &str) -> Option<String> {
+        self.vars.lock().unwrap().get(name).cloned()
+    }
+
+    fn write_var(&self, name: &str, value: &str) -> Result<(), String> {
+        self.vars.lock().unwrap().insert(name.to_string(), value.to_string());
+        Ok(())
+    }
+
+    fn remove_var(&self, name: &str) -> Result<(), String> {
+        self.vars.lock().unwrap().remove(name);
+        Ok(())
+    }
+
+    fn var_names(&self) -> Vec<String> {
+        self.vars.lock().unwrap().keys().cloned().collect()
+    }
+
+    fn broadcast_change(&self) {}
+
+    /// Trockenläufe und Tests sollen die echten Snapshots und Sicherungen nicht anfassen.
+    fn data_dir(&self) -> Option<PathBuf> {
+        None
+    }
+}
+
+// --- JSON DATEI ---
+
+/// Speichert die Variablen als JSON-Objekt in einer Datei.
+pub struct FileBackend {
+    path: PathBuf,
+}
+
+impl FileBackend {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    fn load(&self) -> Result<HashMap<String, String>, String> {
+        if !self.path.exists() {
+            return Ok(HashMap::new());
+        }
+        let file = File::open(&self.path).map_err(|e| e.to_string())?;
+        serde_json::from_reader(BufReader::new(file)).map_err(|e| e.to_string())
+    }
+
+    fn store(&self, vars: &HashMap<String, String>) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let file = File::create(&self.path).map_err(|e| e.to_string())?;
+        serde_json::to_writer_pretty(BufWriter::new(file), vars).map_err(|e| e.to_string())
+    }
+}
+
+impl EnvBackend for FileBackend {
+    fn read_var(&self, name: &str) -> Option<String> {
+        self.load().ok()?.get(name).cloned()
+    }
+
+    fn write_var(&self, name: &str, value: &str) -> Result<(), String> {
+        let mut vars = self.load()?;
+        vars.insert(name.to_string(), value.to_string());
+        self.store(&vars)
+    }
+
+    fn remove_var(&self, name: &str) -> Result<(), String> {
+        let mut vars = self.load()?;
+        if vars.remove(name).is_some() {
+            self.store(&vars)?;
+        }
+        Ok(())
+    }
+
+    fn var_names(&self) -> Vec<String> {
+        self.load().map(|vars| vars.into_keys().collect()).unwrap_or_default()
+    }
+
+    fn broadcast_change(&self) {}
+
+    /// Neben der Datei, z.B. `env.json` → `env.data/`.
+    fn data_dir(&self) -> Option<PathBuf> {
+        Some(self.path.with_extension("data"))
+    }
+}
+
+/// Liefert das passende Backend für das aktuelle Betriebssystem.
+///
+/// Mit `VERSION_SWITCHER_BACKEND=memory` wird nur im Speicher gearbeitet (startet mit dem
+/// PATH des Prozesses). Außerhalb von Windows werden die Shell-Startdateien verwaltet,
+/// außer `VERSION_SWITCHER_ENV_FILE` zeigt auf eine JSON-Datei.
+pub fn default_backend() -> Box<dyn EnvBackend + Send + Sync> {
+    if std::env::var("VERSION_SWITCHER_BACKEND").is_ok_and(|v| v == "memory") {
+        let mut vars = HashMap::new();
+        if let Ok(path) = std::env::var("PATH") {
+            vars.insert(crate::logic::PATH_VAR.to_string(), path);
+        }
+        return Box::new(MemoryBackend::with_vars(vars));
+    }
+
+    #[cfg(windows)]
+    {
+        Box::new(RegistryBackend)
+    }
+    #[cfg(not(windows))]
+    {
+        match std::env::var_os("VERSION_SWITCHER_ENV_FILE") {
+            Some(path) => Box::new(FileBackend::new(path)),
+            None => Box::new(crate::shell_profile::for_current_user()),
+        }
+    }
+}
+
+/// Hilfen für Tests anderer Module.
+#[cfg(test)]
+pub(crate) mod testing {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Speicher-Backend, das Benachrichtigungen zählt und beim Schreiben einer
+    /// bestimmten Variable scheitern kann.
+    pub struct TestBackend {
+        pub inner: MemoryBackend,
+        pub fail_on: Option<&'static str>,
+        pub broadcasts: Cell<usize>,
+    }
+
+    impl TestBackend {
+        pub fn new(inner: MemoryBackend) -> Self {
+            Self { inner, fail_on: None, broadcasts: Cell::new(0) }
+        }
+
+        pub fn failing_on(inner: MemoryBackend, name: &'static str) -> Self {
+            Self { fail_on: Some(name), ..Self::new(inner) }
+        }
+    }
+
+    impl EnvBackend for TestBackend {
+        fn read_var(&self, name: &str) -> Option<String> {
+            self.inner.read_var(name)
+        }
+
+        fn write_var(&self, name: &str, value: &str) -> Result<(), String> {
+            if self.fail_on == Some(name) {
+                return Err(format!("cannot write {}", name));
+            }
+            self.inner.write_var(name, value)
+        }
+
+        fn remove_var(&self, name: &str) -> Result<(), String> {
+            self.inner.remove_var(name)
+        }
+
+        fn var_names(&self) -> Vec<String> {
+            self.inner.var_names()
+        }
+
+        fn broadcast_change(&self) {
+            self.broadcasts.set(self.broadcasts.get() + 1);
+        }
+
+        fn data_dir(&self) -> Option<PathBuf> {
+            None
+        }
+    }
+}
@@ -0,0 +1,5 @@
+// Reine Kommandozeilen-Version, läuft auch ohne grafische Oberfläche (z.B. über SSH)
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    std::process::exit(version_switcher::cli::run(&args));
+}
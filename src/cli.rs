@@ -0,0 +1,794 @@
+use std::path::{Path, PathBuf};
+
+use crate::audit;
+use crate::backend::{self, EnvBackend};
+use crate::config::Config;
+use crate::discovery;
+use crate::env_vars;
+use crate::hooks::{self, HookPhase, HookRun};
+use crate::language::Language;
+use crate::layers;
+use crate::logic::{self, ListValues};
+use crate::path_list::{self, PathList};
+use crate::pins;
+use crate::profiles;
+use crate::probe::{self, Probe};
+use crate::snapshots;
+use crate::types::{Hooks, InsertPosition, PathChange, SwitchMode, VersionEntry};
+use crate::user_env;
+use crate::which;
+
+const USAGE: &str = "\
+Usage: version_switcher <command> [args]
+
+Commands:
+  list                   List all groups and their versions
+  use <group> <alias> [--dry-run]
+                         Activate a version
+  current                Show the active version of every group
+  path                   Show the effective PATH (system, user and session entries)
+  which <command>        Show every match for a command in PATH order (* = used)
+  project [dir] [--dry-run]
+                         Activate the versions pinned for a project directory
+  profile                List profiles (* = all versions active)
+  profile save <name> [group=alias...]
+                         Save a profile (without pairs: the active versions)
+  profile use <name> [--dry-run]
+                         Activate all versions of a profile at once
+  profile remove <name>  Delete a profile
+  discover [--add] [dir...]
+                         Search for installed toolchains (and add them)
+  probe [group]          Detect the real version of every entry
+  probe set <group> <executable> [args...]
+                         Define how a group's version is detected
+  probe unset <group>    Remove the version detection of a group
+  mode [path|shims]      Show or set how versions are activated
+  position <group> [prepend|append|replace|before <entry>|after <entry>]
+                         Show or set where a group's version goes in PATH
+  var <group> [name]     Show or set the list variable a group manages (PATH, PYTHONPATH, ...)
+  hooks <group> [alias]  Show the commands that run before/after activating a version
+  hooks <group> [alias] pre|post <command>
+                         Add a hook for the group (or one version), ${path} = version folder
+  hooks <group> [alias] clear
+                         Remove the hooks of the group (or one version)
+  env                    List the user's environment variables (% = expandable)
+  env set <name> <value> [--expand|--literal]
+                         Create or change a variable (default: keep its kind)
+  env remove <name>      Delete a variable
+  clean [--dry-run]      Remove missing and duplicate entries (PATH and all managed variables)
+  clean --audit [file]   List risky PATH entries (and save them as a JSON report)
+  compact [--dry-run]    Shorten PATH (remove duplicates, use %VAR% / $HOME for known folders)
+  length [n]             Show the PATH length or set the warning threshold to n characters
+  snapshots              List saved PATH and variable values (newest first)
+  snapshots show <n>     Compare snapshot <n> with the current value
+  snapshots restore <n>  Restore snapshot <n>
+  export [file]          Export the configuration as JSON (stdout if no file)
+  import <file>          Import a configuration from a JSON file
+  help                   Show this help
+
+--dry-run shows the PATH changes (+ added, - removed, ~ moved) and warnings without writing.";
+
+/// Führt einen Befehl ohne GUI aus und liefert den Exit-Code.
+///
+/// `args` sind die Argumente ohne den Programmnamen, z.B. `["use", "Python", "3.11"]`.
+pub fn run(args: &[String]) -> i32 {
+    attach_console();
+
+    let backend = backend::default_backend();
+    // Eine beschädigte Konfiguration nicht mit den Standardwerten überschreiben
+    let mut config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("{}", Language::English.status_error(&format!("Could not read the configuration: {}", e)));
+            return 1;
+        }
+    };
+
+    let args: Vec<&str> = args.iter().map(|s| s.as_str()).collect();
+    let result = match args.as_slice() {
+        ["list"] => cmd_list(&config, backend.as_ref()),
+        ["use", group, alias] => cmd_use(&mut config, backend.as_ref(), group, alias, false),
+        ["use", group, alias, "--dry-run"] => cmd_use(&mut config, backend.as_ref(), group, alias, true),
+        ["current"] => cmd_current(&config, backend.as_ref()),
+        ["path"] => cmd_path(&config, backend.as_ref()),
+        ["which", command] => cmd_which(&config, backend.as_ref(), command),
+        ["project", rest @ ..] => {
+            let dry_run = rest.contains(&"--dry-run");
+            let dir = rest.iter().find(|a| **a != "--dry-run").copied().unwrap_or(".");
+            cmd_project(&mut config, backend.as_ref(), Path::new(dir), dry_run)
+        }
+        ["profile"] | ["profile", "list"] => cmd_profiles(&config, backend.as_ref()),
+        ["profile", "save", name, pairs @ ..] => cmd_profile_save(&mut config, backend.as_ref(), name, pairs),
+        ["profile", "use", name] => cmd_profile_use(&mut config, backend.as_ref(), name, false),
+        ["profile", "use", name, "--dry-run"] => cmd_profile_use(&mut config, backend.as_ref(), name, true),
+        ["profile", "remove", name] => cmd_profile_remove(&mut config, name),
+        ["discover", rest @ ..] => cmd_discover(&mut config, rest),
+        ["probe", "set", group, exe, rest @ ..] => cmd_probe_set(&mut config, group, exe, rest),
+        ["probe", "unset", group] => {
+            config.set_probe(group, None);
+            config.save()
+        }
+        ["probe"] => cmd_probe(&mut config, None),
+        ["probe", group] => cmd_probe(&mut config, Some(group)),
+        ["mode"] => {
+            println!("{:?}", config.switch_mode);
+            Ok(())
+        }
+        ["mode", mode] => cmd_mode(&mut config, mode),
+        ["position", group] => cmd_position(&config, group),
+        ["position", group, rest @ ..] => cmd_set_position(&mut config, group, rest),
+        ["var", group] => cmd_var(&config, group),
+        ["var", group, name] => cmd_set_var(&mut config, group, name),
+        ["hooks", group] => cmd_hooks(&config, group, None),
+        ["hooks", group, "clear"] => cmd_hooks_clear(&mut config, group, None),
+        ["hooks", group, phase @ ("pre" | "post"), command] => cmd_hook_add(&mut config, group, None, phase, command),
+        ["hooks", group, alias] => cmd_hooks(&config, group, Some(alias)),
+        ["hooks", group, alias, "clear"] => cmd_hooks_clear(&mut config, group, Some(alias)),
+        ["hooks", group, alias, phase @ ("pre" | "post"), command] => cmd_hook_add(&mut config, group, Some(alias), phase, command),
+        ["env"] => cmd_env(backend.as_ref()),
+        ["env", "set", name, value] => cmd_env_set(&mut config, backend.as_ref(), name, value, None),
+        ["env", "set", name, value, "--expand"] => cmd_env_set(&mut config, backend.as_ref(), name, value, Some(true)),
+        ["env", "set", name, value, "--literal"] => cmd_env_set(&mut config, backend.as_ref(), name, value, Some(false)),
+        ["env", "remove", name] => cmd_env_remove(&mut config, backend.as_ref(), name),
+        ["clean"] => cmd_clean(&mut config, backend.as_ref(), false),
+        ["clean", "--dry-run"] => cmd_clean(&mut config, backend.as_ref(), true),
+        ["clean", "--audit"] => cmd_audit(&config, backend.as_ref(), None),
+        ["clean", "--audit", file] => cmd_audit(&config, backend.as_ref(), Some(Path::new(file))),
+        ["compact"] => cmd_compact(&mut config, backend.as_ref(), false),
+        ["compact", "--dry-run"] => cmd_compact(&mut config, backend.as_ref(), true),
+        ["length"] => cmd_length(&config, backend.as_ref()),
+        ["length", n] => cmd_set_length(&mut config, n),
+        ["snapshots"] => cmd_snapshots(backend.as_ref()),
+        ["snapshots", "show", n] => cmd_snapshot_show(backend.as_ref(), n),
+        ["snapshots", "restore", n] => cmd_snapshot_restore(&mut config, backend.as_ref(), n),
+        ["export"] => cmd_export(&mut config, None),
+        ["export", file] => cmd_export(&mut config, Some(Path::new(file))),
+        ["import", file] => cmd_import(&mut config, Path::new(file)),
+        [] | ["help"] | ["--help"] | ["-h"] => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+        _ => Err(format!("Unknown command: {}\n\n{}", args.join(" "), USAGE)),
+    };
+
+    match result {
+        Ok(_) => 0,
+        Err(e) => {
+            eprintln!("{}", config.app_language.status_error(&e));
+            1
+        }
+    }
+}
+
+// Im Release-Build hat das Programm unter Windows keine eigene Konsole,
+// daher hängen wir uns an die Konsole des Aufrufers.
+fn attach_console() {
+    #[cfg(windows)]
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
+
+#[cfg(windows)]
+const ATTACH_PARENT_PROCESS: u32 = u32::MAX;
+
+#[cfg(windows)]
+#[link(name = "kernel32")]
+unsafe extern "system" {
+    fn AttachConsole(process_id: u32) -> i32;
+}
+
+fn sorted_groups(config: &Config) -> Vec<&String> {
+    let mut groups: Vec<&String> = config.languages.keys().collect();
+    groups.sort();
+    groups
+}
+
+/// Aktuelle Werte aller Listen-Variablen, die Gruppen verwalten.
+fn managed_values(config: &Config, backend: &dyn EnvBackend) -> ListValues {
+    logic::read_lists(backend, &config.managed_vars())
+}
+
+fn cmd_list(config: &Config, backend: &dyn EnvBackend) -> Result<(), String> {
+    let values = managed_values(config, backend);
+
+    for group in sorted_groups(config) {
+        let versions = &config.languages[group];
+        let target = config.group_target(group);
+        let active = logic::current_entry(config.switch_mode, &values, group, versions, &target);
+        if target.is_path() {
+            println!("{}", group);
+        } else {
+            println!("{} ({})", group, target.var);
+        }
+        for v in versions {
+            let marker = if active.is_some_and(|a| a.path == v.path) { "*" } else { " " };
+            println!("  {} {:<16} {}", marker, v.alias, v.dirs_string());
+            for (var, dirs) in &v.lists {
+                println!("    {:<16} {}={}", "", var, dirs.join(&path_list::SEPARATOR.to_string()));
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_changes(changes: &[PathChange]) {
+    for change in changes {
+        match change {
+            PathChange::Kept(p) => println!("  {}", p),
+            PathChange::Added(p) => println!("+ {}", p),
+            PathChange::Removed(p) => println!("- {}", p),
+            PathChange::Moved(p) => println!("~ {}", p),
+        }
+    }
+}
+
+fn print_preview(preview: &logic::PathPreview) {
+    if preview.is_unchanged() {
+        println!("{} unchanged", preview.var);
+    } else {
+        if preview.var != logic::PATH_VAR {
+            println!("{}:", preview.var);
+        }
+        print_changes(&preview.changes);
+    }
+    for warning in &preview.warnings {
+        eprintln!("! {}", warning.describe());
+    }
+}
+
+fn print_previews(previews: &[logic::PathPreview]) {
+    for preview in previews {
+        print_preview(preview);
+    }
+}
+
+/// Zeigt bei `--dry-run`, welche Hooks laufen würden.
+fn print_hook_plan(config: &Config, versions: &[(String, VersionEntry)]) {
+    for phase in [HookPhase::Pre, HookPhase::Post] {
+        for (group, entry) in versions {
+            for command in hooks::commands(config.hooks.get(group), entry, phase) {
+                println!("{}-hook {}: {}", phase.describe(), group, env_vars::expand_value(command, entry));
+            }
+        }
+    }
+}
+
+/// Gibt die Hook-Läufe aus und schreibt sie in den Verlauf, auch wenn nicht umgeschaltet wurde.
+fn record_hooks(config: &mut Config, runs: &[HookRun], result: Result<(), String>) -> Result<(), String> {
+    for run in runs {
+        if run.succeeded() {
+            println!("{}", run.describe());
+        } else {
+            eprintln!("! {}", run.describe());
+        }
+        config.add_to_history(run.describe());
+    }
+    if result.is_err() {
+        // Der Verlauf ist nur Beiwerk, der eigentliche Fehler geht vor
+        if let Err(e) = config.save() {
+            eprintln!("! {}", e);
+        }
+    }
+    result
+}
+
+/// Meldet auf stderr, wenn der Benutzer-PATH die eingestellte Länge überschreitet.
+fn warn_length(config: &Config, backend: &dyn EnvBackend) {
+    if let Some(warning) = logic::length_warning(&logic::get_current_path_var(backend), config.path_length_warning) {
+        eprintln!("! {} (try `version_switcher compact`)", warning.describe());
+    }
+}
+
+fn cmd_use(config: &mut Config, backend: &dyn EnvBackend, group: &str, alias: &str, dry_run: bool) -> Result<(), String> {
+    let entry = config.find_entry(group, alias)?.clone();
+    let versions = [(group.to_string(), entry.clone())];
+    if dry_run {
+        let values = managed_values(config, backend);
+        let planned = logic::planned_lists(&values, config.switch_mode, &config.languages[group], &entry, &config.group_target(group))?;
+        print_previews(&logic::preview_lists(&values, &planned, config.path_length_warning));
+        print_hook_plan(config, &versions);
+        return Ok(());
+    }
+    let (runs, result) = hooks::with_hooks(backend, &config.hooks, &versions, || {
+        logic::activate(backend, config.switch_mode, group, &config.languages[group], &entry, &config.group_target(group))
+    });
+    record_hooks(config, &runs, result)?;
+    println!("{}", config.app_language.status_activated(&entry.path));
+    warn_length(config, backend);
+
+    config.add_to_history(format!("Activated: {} ({})", alias, group));
+    config.save()
+}
+
+fn cmd_current(config: &Config, backend: &dyn EnvBackend) -> Result<(), String> {
+    let values = managed_values(config, backend);
+    let targets = config.group_targets();
+    let effective = layers::current_effective(backend);
+    let shadows = layers::shadowed_versions(&effective, config.switch_mode, &values, &config.languages, &targets);
+
+    for group in sorted_groups(config) {
+        match logic::current_entry(config.switch_mode, &values, group, &config.languages[group], &logic::group_target(&targets, group)) {
+            Some(v) => {
+                println!("{}: {} ({})", group, v.alias, v.dirs_string());
+                for shadow in shadows.get(&v.path).into_iter().flatten() {
+                    let layer = config.app_language.layer_name(shadow.by.layer);
+                    eprintln!("  ! {} is shadowed by {} [{}]", shadow.name, shadow.by.entry, layer);
+                }
+            }
+            None => println!("{}: -", group),
+        }
+    }
+    for name in profiles::matching(&config.profiles, config.switch_mode, &values, &config.languages, &targets) {
+        println!("Profile: {}", name);
+    }
+    Ok(())
+}
+
+fn cmd_path(config: &Config, backend: &dyn EnvBackend) -> Result<(), String> {
+    for e in layers::current_effective(backend) {
+        println!("[{:<8}] {}", config.app_language.layer_name(e.layer), e.entry);
+    }
+    Ok(())
+}
+
+fn cmd_which(config: &Config, backend: &dyn EnvBackend, command: &str) -> Result<(), String> {
+    let effective = layers::current_effective(backend);
+    let matches = which::resolve(&effective, command, &config.languages);
+    if matches.is_empty() {
+        return Err(format!("{}: not found in PATH", command));
+    }
+
+    for (i, m) in matches.iter().enumerate() {
+        let marker = if i == 0 { '*' } else { ' ' };
+        let layer = config.app_language.layer_name(m.source.layer);
+        let owner = match &m.owner {
+            Some(o) if o.via_shim => format!(" ({} {}, shim)", o.group, o.alias),
+            Some(o) => format!(" ({} {})", o.group, o.alias),
+            None => String::new(),
+        };
+        println!("{} {} [{}]{}", marker, m.file.display(), layer, owner);
+    }
+    Ok(())
+}
+
+fn cmd_project(config: &mut Config, backend: &dyn EnvBackend, dir: &Path, dry_run: bool) -> Result<(), String> {
+    let dir = dir.canonicalize().map_err(|e| format!("{}: {}", dir.display(), e))?;
+    let pins = pins::find_pins(&dir)?;
+    if pins.is_empty() {
+        return Err(format!("No version files found in {} or above", dir.display()));
+    }
+
+    let resolution = pins::resolve_pins(&pins, &config.languages);
+    for (group, entry) in &resolution.resolved {
+        println!("{}: {} ({})", group, entry.alias, entry.path);
+    }
+    for problem in &resolution.problems {
+        eprintln!("{}", problem.describe());
+    }
+    if resolution.resolved.is_empty() {
+        return Ok(());
+    }
+    if dry_run {
+        let values = managed_values(config, backend);
+        let planned = pins::planned_lists(&values, config.switch_mode, &resolution, &config.languages, &config.group_targets())?;
+        print_previews(&logic::preview_lists(&values, &planned, config.path_length_warning));
+        print_hook_plan(config, &resolution.resolved);
+        return Ok(());
+    }
+
+    let (runs, result) = hooks::with_hooks(backend, &config.hooks, &resolution.resolved, || {
+        pins::activate_resolution(backend, config.switch_mode, &resolution, &config.languages, &config.group_targets(), "Project")
+    });
+    record_hooks(config, &runs, result)?;
+    warn_length(config, backend);
+    config.add_to_history(format!("Activated project: {}", dir.display()));
+    config.save()
+}
+
+fn cmd_profiles(config: &Config, backend: &dyn EnvBackend) -> Result<(), String> {
+    let values = managed_values(config, backend);
+    let targets = config.group_targets();
+    for (name, profile) in &config.profiles {
+        let marker = if profiles::matches(profile, config.switch_mode, &values, &config.languages, &targets) { '*' } else { ' ' };
+        let versions: Vec<String> = profile.iter().map(|(g, a)| format!("{}={}", g, a)).collect();
+        println!("{} {}: {}", marker, name, versions.join(" "));
+    }
+    Ok(())
+}
+
+fn cmd_profile_save(config: &mut Config, backend: &dyn EnvBackend, name: &str, pairs: &[&str]) -> Result<(), String> {
+    let profile: profiles::Profile = if pairs.is_empty() {
+        profiles::from_current(config.switch_mode, &managed_values(config, backend), &config.languages, &config.group_targets())
+    } else {
+        pairs.iter()
+            .map(|p| p.split_once('=').map(|(g, a)| (g.to_string(), a.to_string())).ok_or_else(|| format!("Expected group=alias: {}", p)))
+            .collect::<Result<_, _>>()?
+    };
+    // Prüfen, bevor gespeichert wird
+    profiles::resolve(&profile, &config.languages)?;
+
+    println!("{}", config.app_language.status_profile_saved(name, profile.len()));
+    config.profiles.insert(name.to_string(), profile);
+    config.add_to_history(format!("Saved profile: {}", name));
+    config.save()
+}
+
+fn cmd_profile_use(config: &mut Config, backend: &dyn EnvBackend, name: &str, dry_run: bool) -> Result<(), String> {
+    let profile = config.profiles.get(name).ok_or_else(|| format!("Unknown profile: {}", name))?.clone();
+    let versions = profiles::resolve(&profile, &config.languages)?.resolved;
+    if dry_run {
+        let values = managed_values(config, backend);
+        let planned = profiles::planned_lists(&values, config.switch_mode, &profile, &config.languages, &config.group_targets())?;
+        print_previews(&logic::preview_lists(&values, &planned, config.path_length_warning));
+        print_hook_plan(config, &versions);
+        return Ok(());
+    }
+
+    let (runs, result) = hooks::with_hooks(backend, &config.hooks, &versions, || {
+        profiles::activate(backend, config.switch_mode, name, &profile, &config.languages, &config.group_targets())
+    });
+    record_hooks(config, &runs, result)?;
+    println!("{}", config.app_language.status_profile_activated(name));
+    warn_length(config, backend);
+    config.add_to_history(format!("Activated profile: {}", name));
+    config.save()
+}
+
+fn cmd_profile_remove(config: &mut Config, name: &str) -> Result<(), String> {
+    config.profiles.remove(name).ok_or_else(|| format!("Unknown profile: {}", name))?;
+    config.add_to_history(format!("Deleted profile: {}", name));
+    config.save()
+}
+
+fn cmd_discover(config: &mut Config, args: &[&str]) -> Result<(), String> {
+    let add = args.contains(&"--add");
+    let mut roots = discovery::well_known_roots();
+    roots.extend(config.discovery_roots.iter().map(PathBuf::from));
+    roots.extend(args.iter().filter(|a| **a != "--add").map(PathBuf::from));
+
+    let candidates = discovery::discover(&roots, &config.languages);
+    for c in &candidates {
+        println!("{:<10} {:<16} {}", c.group, c.entry.alias, c.entry.dirs_string());
+    }
+    if candidates.is_empty() {
+        println!("{}", config.app_language.label_no_candidates());
+    }
+    if !add || candidates.is_empty() {
+        return Ok(());
+    }
+
+    let count = discovery::add_selected(&mut config.languages, &candidates);
+    println!("{}", config.app_language.status_discovered(count));
+    config.add_to_history(format!("Discovered: {} versions added", count));
+    config.save()
+}
+
+fn cmd_probe(config: &mut Config, only: Option<&str>) -> Result<(), String> {
+    if let Some(group) = only
+        && !config.probes.contains_key(group)
+    {
+        return Err(format!("No probe defined for group {}", group));
+    }
+
+    let jobs: Vec<(String, Probe)> = config.stale_probes();
+    for (dir, probe) in jobs {
+        let result = probe::run_probe(&probe, &dir);
+        config.probe_cache.insert(dir, result);
+    }
+
+    let mut groups: Vec<&String> = config.probes.keys().filter(|g| only.is_none_or(|o| o == g.as_str())).collect();
+    groups.sort();
+    for group in groups {
+        println!("{}", group);
+        for v in config.languages.get(group).into_iter().flatten() {
+            let result = config.probe_cache.get(&v.path);
+            let (marker, detected) = match result {
+                Some(probe::ProbeResult { version: Some(found), .. }) => {
+                    let marker = if probe::is_mismatch(&v.alias, found) { "!" } else { " " };
+                    (marker, found.clone())
+                }
+                Some(probe::ProbeResult { error: Some(e), .. }) => ("?", e.clone()),
+                _ => ("?", "-".to_string()),
+            };
+            println!("  {} {:<16} {:<16} {}", marker, v.alias, detected, v.path);
+        }
+    }
+    config.save()
+}
+
+fn cmd_probe_set(config: &mut Config, group: &str, exe: &str, args: &[&str]) -> Result<(), String> {
+    if !config.languages.contains_key(group) {
+        return Err(format!("Unknown group: {}", group));
+    }
+    let mut probe = Probe { executable: exe.to_string(), ..Default::default() };
+    if !args.is_empty() {
+        probe.args = args.join(" ");
+    }
+    config.set_probe(group, Some(probe));
+    config.save()
+}
+
+fn cmd_mode(config: &mut Config, mode: &str) -> Result<(), String> {
+    config.switch_mode = match mode {
+        "path" => SwitchMode::Path,
+        "shims" => SwitchMode::Shims,
+        _ => return Err(format!("Unknown mode: {} (expected path or shims)", mode)),
+    };
+    config.save()
+}
+
+fn cmd_position(config: &Config, group: &str) -> Result<(), String> {
+    if !config.languages.contains_key(group) {
+        return Err(format!("Unknown group: {}", group));
+    }
+    println!("{}", config.insert_position(group).describe());
+    Ok(())
+}
+
+fn cmd_set_position(config: &mut Config, group: &str, args: &[&str]) -> Result<(), String> {
+    if !config.languages.contains_key(group) {
+        return Err(format!("Unknown group: {}", group));
+    }
+    let position = match args {
+        ["prepend"] => InsertPosition::Prepend,
+        ["append"] => InsertPosition::Append,
+        ["replace"] => InsertPosition::Replace,
+        ["before", entry] => InsertPosition::Before(entry.to_string()),
+        ["after", entry] => InsertPosition::After(entry.to_string()),
+        _ => return Err(format!("Unknown position: {} (expected prepend, append, replace, before <entry> or after <entry>)", args.join(" "))),
+    };
+    config.add_to_history(format!("Insert position of {}: {}", group, position.describe()));
+    config.set_insert_position(group, position);
+    config.save()
+}
+
+fn cmd_var(config: &Config, group: &str) -> Result<(), String> {
+    if !config.languages.contains_key(group) {
+        return Err(format!("Unknown group: {}", group));
+    }
+    println!("{}", config.target_var(group));
+    Ok(())
+}
+
+fn cmd_set_var(config: &mut Config, group: &str, name: &str) -> Result<(), String> {
+    if !config.languages.contains_key(group) {
+        return Err(format!("Unknown group: {}", group));
+    }
+    if name.is_empty() || name.contains('=') || name.contains(char::is_whitespace) {
+        return Err(format!("Invalid variable name: {}", name));
+    }
+    config.set_target_var(group, name);
+    config.add_to_history(format!("Variable of {}: {}", group, config.target_var(group)));
+    config.save()
+}
+
+fn cmd_hooks(config: &Config, group: &str, alias: Option<&str>) -> Result<(), String> {
+    let versions = config.languages.get(group).ok_or_else(|| format!("Unknown group: {}", group))?;
+    let print = |label: String, hooks: &Hooks| {
+        if hooks.is_empty() {
+            return;
+        }
+        println!("{}", label);
+        for command in &hooks.pre {
+            println!("  pre   {}", command);
+        }
+        for command in &hooks.post {
+            println!("  post  {}", command);
+        }
+    };
+    match alias {
+        Some(alias) => print(format!("{} {}", group, alias), &config.find_entry(group, alias)?.hooks),
+        None => {
+            if let Some(hooks) = config.hooks.get(group) {
+                print(group.to_string(), hooks);
+            }
+            for v in versions {
+                print(format!("{} {}", group, v.alias), &v.hooks);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn cmd_hook_add(config: &mut Config, group: &str, alias: Option<&str>, phase: &str, command: &str) -> Result<(), String> {
+    if command.trim().is_empty() {
+        return Err("The command is empty".to_string());
+    }
+    let hooks = match alias {
+        Some(alias) => &mut config.find_entry_mut(group, alias)?.hooks,
+        None if config.languages.contains_key(group) => config.hooks.entry(group.to_string()).or_default(),
+        None => return Err(format!("Unknown group: {}", group)),
+    };
+    match phase {
+        "pre" => hooks.pre.push(command.to_string()),
+        _ => hooks.post.push(command.to_string()),
+    }
+    let target = alias.map(|a| format!("{} {}", group, a)).unwrap_or_else(|| group.to_string());
+    config.add_to_history(format!("Hook added: {} ({}-hook): {}", target, phase, command));
+    config.save()
+}
+
+fn cmd_hooks_clear(config: &mut Config, group: &str, alias: Option<&str>) -> Result<(), String> {
+    match alias {
+        Some(alias) => config.find_entry_mut(group, alias)?.hooks = Hooks::default(),
+        None if config.languages.contains_key(group) => config.set_hooks(group, Hooks::default()),
+        None => return Err(format!("Unknown group: {}", group)),
+    }
+    let target = alias.map(|a| format!("{} {}", group, a)).unwrap_or_else(|| group.to_string());
+    config.add_to_history(format!("Hooks removed: {}", target));
+    config.save()
+}
+
+fn cmd_env(backend: &dyn EnvBackend) -> Result<(), String> {
+    for var in user_env::list(backend) {
+        let marker = if var.expandable { '%' } else { ' ' };
+        println!("{} {}={}", marker, var.name, var.value);
+    }
+    Ok(())
+}
+
+fn cmd_env_set(config: &mut Config, backend: &dyn EnvBackend, name: &str, value: &str, expandable: Option<bool>) -> Result<(), String> {
+    // Ohne Angabe bleibt die Art einer vorhandenen Variable, neue richten sich nach dem Wert
+    let expandable = expandable.unwrap_or_else(|| match backend.read_var(name) {
+        Some(_) => backend.is_expandable(name),
+        None => backend::has_references(value),
+    });
+    user_env::set_var(backend, name, value, expandable, "Edit variable")?;
+    let var = user_env::UserVar { name: name.to_string(), value: value.to_string(), expandable };
+    println!("{}", config.app_language.status_var_set(name));
+    config.add_to_history(format!("Set variable: {}", var.describe()));
+    config.save()
+}
+
+fn cmd_env_remove(config: &mut Config, backend: &dyn EnvBackend, name: &str) -> Result<(), String> {
+    if backend.read_var(name).is_none() {
+        return Err(format!("Unknown variable: {}", name));
+    }
+    user_env::remove_var(backend, name, "Delete variable")?;
+    println!("{}", config.app_language.status_var_removed(name));
+    config.add_to_history(format!("Deleted variable: {}", name));
+    config.save()
+}
+
+fn cmd_clean(config: &mut Config, backend: &dyn EnvBackend, dry_run: bool) -> Result<(), String> {
+    let lang = config.app_language;
+    let values = managed_values(config, backend);
+    let issues = logic::scan_all(config.switch_mode, &values, &config.languages, &config.group_targets());
+
+    if issues.is_empty() {
+        println!("{}", lang.label_no_issues());
+        return Ok(());
+    }
+    for entry in &issues {
+        let detail = lang.issue_detail(&entry.issue).map(|d| format!(" ({})", d)).unwrap_or_default();
+        // Nicht ausgewählte Einträge werden nur gemeldet
+        let fix = entry.issue.fix()
+            .filter(|_| entry.selected)
+            .map(|f| format!(" -> {}", lang.fix_label(&f)))
+            .unwrap_or_default();
+        let var = if entry.var == logic::PATH_VAR { String::new() } else { format!("{}: ", entry.var) };
+        println!("[{}] {}{}{}{}", lang.issue_label(&entry.issue), var, entry.path, detail, fix);
+    }
+    if dry_run {
+        println!();
+        let (planned, _) = logic::cleanup_all(&values, &issues);
+        print_previews(&logic::preview_lists(&values, &planned, config.path_length_warning));
+        return Ok(());
+    }
+
+    let count = logic::run_cleaner(backend, &issues)?;
+    println!("{}", lang.status_cleaned(count));
+    warn_length(config, backend);
+    if count > 0 {
+        config.add_to_history(format!("Cleaned {} entries", count));
+        config.save()?;
+    }
+    Ok(())
+}
+
+fn cmd_compact(config: &mut Config, backend: &dyn EnvBackend, dry_run: bool) -> Result<(), String> {
+    let current = logic::get_current_path_var(backend);
+    let compaction = logic::compact_path(&current);
+    let saved = compaction.saved(&current);
+    for (reference, value) in &compaction.substitutions {
+        println!("{} = {}", reference, value);
+    }
+    println!("{}", config.app_language.status_compacted(compaction.duplicates, saved));
+
+    if dry_run {
+        println!();
+        print_preview(&logic::preview_path(&current, &compaction.path, config.path_length_warning));
+        return Ok(());
+    }
+    if compaction.path != current {
+        logic::set_path_var(backend, compaction.path, "Compact")?;
+        config.add_to_history(format!("Compacted PATH ({} characters saved)", saved));
+        config.save()?;
+    }
+    Ok(())
+}
+
+fn cmd_length(config: &Config, backend: &dyn EnvBackend) -> Result<(), String> {
+    let length = logic::path_length(&logic::get_current_path_var(backend));
+    println!("{} / {} (warning) / {} (maximum)", length, config.path_length_warning, logic::PATH_HARD_LIMIT);
+    warn_length(config, backend);
+    Ok(())
+}
+
+fn cmd_set_length(config: &mut Config, n: &str) -> Result<(), String> {
+    config.path_length_warning = n.parse().map_err(|_| format!("Invalid length: {}", n))?;
+    config.save()
+}
+
+fn cmd_audit(config: &Config, backend: &dyn EnvBackend, report: Option<&Path>) -> Result<(), String> {
+    let lang = config.app_language;
+    let findings = audit::audit_current(backend);
+
+    if findings.is_empty() {
+        println!("{}", lang.label_no_findings());
+    }
+    for f in &findings {
+        println!("[{}] [{}] {}: {}", lang.severity_name(f.severity), lang.layer_name(f.layer), f.entry, f.describe());
+    }
+    if let Some(path) = report {
+        audit::export_to_path(&findings, path)?;
+        println!("{}", lang.status_report_exported());
+    }
+    Ok(())
+}
+
+fn cmd_snapshots(backend: &dyn EnvBackend) -> Result<(), String> {
+    let snapshots = snapshots::load(backend);
+    for (idx, s) in snapshots.iter().enumerate() {
+        let count = PathList::parse(&s.value).len();
+        println!("{:>3}  {}  {:<16} {:<24} {} entries", idx, s.time, s.var, s.reason, count);
+    }
+    Ok(())
+}
+
+fn find_snapshot(backend: &dyn EnvBackend, n: &str) -> Result<snapshots::Snapshot, String> {
+    let idx: usize = n.parse().map_err(|_| format!("Invalid snapshot number: {}", n))?;
+    snapshots::load(backend).into_iter().nth(idx).ok_or_else(|| format!("Unknown snapshot: {}", idx))
+}
+
+fn cmd_snapshot_show(backend: &dyn EnvBackend, n: &str) -> Result<(), String> {
+    let snapshot = find_snapshot(backend, n)?;
+    // Was sich ändern würde, wenn man den Stand zurückholt
+    print_changes(&logic::diff_paths(&logic::get_list_var(backend, &snapshot.var), &snapshot.value));
+    Ok(())
+}
+
+fn cmd_snapshot_restore(config: &mut Config, backend: &dyn EnvBackend, n: &str) -> Result<(), String> {
+    let snapshot = find_snapshot(backend, n)?;
+    snapshots::restore(backend, &snapshot)?;
+    println!("{}", config.app_language.status_restored(&snapshot.var, &snapshot.time));
+    warn_length(config, backend);
+    config.add_to_history(format!("Restored {} from {}", snapshot.var, snapshot.time));
+    config.save()
+}
+
+fn cmd_export(config: &mut Config, file: Option<&Path>) -> Result<(), String> {
+    match file {
+        Some(path) => {
+            logic::export_to_path(&config.languages, path)?;
+            eprintln!("{}", config.app_language.status_export_ok());
+            config.add_to_history("Configuration Exported".to_string());
+            config.save()
+        }
+        None => {
+            let json = serde_json::to_string_pretty(&config.languages).map_err(|e| e.to_string())?;
+            println!("{}", json);
+            Ok(())
+        }
+    }
+}
+
+fn cmd_import(config: &mut Config, file: &Path) -> Result<(), String> {
+    let data = logic::import_from_path(file)?;
+    config.replace_groups(data);
+    config.add_to_history("Configuration Imported".to_string());
+    config.save()?;
+    println!("{}", config.app_language.status_import_ok());
+    Ok(())
+}
@@ -0,0 +1,312 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use chrono::Local;
+
+use crate::language::Language;
+use crate::probe::{Probe, ProbeResult};
+use crate::profiles::Profile;
+use crate::logic;
+use crate::types::{GroupTarget, HistoryEntry, Hooks, InsertPosition, SwitchMode, VersionEntry};
+
+/// Name, unter dem eframe die Einstellungen ablegt (Fenstertitel in `main.rs`).
+pub const APP_ID: &str = "Version Switcher";
+/// Schlüssel der App-Daten in `app.ron` (entspricht `eframe::APP_KEY`).
+const APP_KEY: &str = "app";
+
+/// Alles, was zwischen zwei Starts gespeichert wird. GUI und CLI teilen sich diese Daten.
+#[derive(Deserialize, Serialize)]
+#[serde(default)]
+pub struct Config {
+    pub languages: HashMap<String, Vec<VersionEntry>>,
+    /// Benannte Kombinationen aus je einer Version pro Gruppe.
+    pub profiles: BTreeMap<String, Profile>,
+    pub selected_group: String,
+    pub app_language: Language,
+    pub accent_color: [u8; 3],
+    pub switch_mode: SwitchMode,
+    /// Wo die Ordner einer Gruppe in den PATH kommen (Schlüssel: Gruppe, Standard: vorne).
+    pub insert_positions: HashMap<String, InsertPosition>,
+    /// Listen-Variable einer Gruppe, falls nicht PATH (z.B. `PYTHONPATH`, Schlüssel: Gruppe).
+    pub target_vars: HashMap<String, String>,
+    /// Vor jedem Schreiben des PATH eine Vorschau zeigen und bestätigen lassen.
+    pub confirm_changes: bool,
+    /// Ab dieser Länge des Benutzer-PATH wird gewarnt.
+    pub path_length_warning: usize,
+    /// Zusätzliche Ordner, die "Discover" nach Toolchains durchsucht.
+    pub discovery_roots: Vec<String>,
+    /// Wie die echte Version einer Gruppe abgefragt wird (Schlüssel: Gruppe).
+    pub probes: HashMap<String, Probe>,
+    /// Zuletzt erkannte Versionen (Schlüssel: Ordner der Version).
+    pub probe_cache: HashMap<String, ProbeResult>,
+    /// Befehle vor/nach dem Aktivieren, die für alle Versionen einer Gruppe gelten (Schlüssel: Gruppe).
+    pub hooks: HashMap<String, Hooks>,
+
+    pub history: Vec<HistoryEntry>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            languages: HashMap::new(),
+            profiles: BTreeMap::new(),
+            selected_group: "General".to_owned(),
+            app_language: Language::German,
+            accent_color: [255, 140, 0],
+            switch_mode: SwitchMode::Path,
+            insert_positions: HashMap::new(),
+            target_vars: HashMap::new(),
+            confirm_changes: false,
+            path_length_warning: crate::logic::DEFAULT_PATH_LENGTH_WARNING,
+            discovery_roots: Vec::new(),
+            probes: HashMap::new(),
+            probe_cache: HashMap::new(),
+            hooks: HashMap::new(),
+            history: Vec::new(),
+        }
+    }
+}
+
+impl Config {
+    /// Fügt eine Meldung mit Uhrzeit oben in den Verlauf ein (maximal 100 Einträge).
+    pub fn add_to_history(&mut self, message: String) {
+        let time_str = Local::now().format("%H:%M:%S").to_string();
+        self.history.insert(0, HistoryEntry {
+            time: time_str,
+            message,
+        });
+        if self.history.len() > 100 {
+            self.history.pop();
+        }
+    }
+
+    /// Sucht eine Version anhand von Gruppe und Alias.
+    pub fn find_entry(&self, group: &str, alias: &str) -> Result<&VersionEntry, String> {
+        self.languages.get(group)
+            .ok_or_else(|| format!("Unknown group: {}", group))?
+            .iter()
+            .find(|v| v.alias == alias)
+            .ok_or_else(|| format!("Unknown version '{}' in group {}", alias, group))
+    }
+
+    /// Wie `find_entry`, aber zum Ändern.
+    pub fn find_entry_mut(&mut self, group: &str, alias: &str) -> Result<&mut VersionEntry, String> {
+        self.languages.get_mut(group)
+            .ok_or_else(|| format!("Unknown group: {}", group))?
+            .iter_mut()
+            .find(|v| v.alias == alias)
+            .ok_or_else(|| format!("Unknown version '{}' in group {}", alias, group))
+    }
+
+    /// Wo die Ordner einer Gruppe in den PATH kommen.
+    pub fn insert_position(&self, group: &str) -> InsertPosition {
+        self.insert_positions.get(group).cloned().unwrap_or_default()
+    }
+
+    /// Setzt die Einfügeposition einer Gruppe. Der Standard wird nicht gespeichert.
+    pub fn set_insert_position(&mut self, group: &str, position: InsertPosition) {
+        if position == InsertPosition::default() {
+            self.insert_positions.remove(group);
+        } else {
+            self.insert_positions.insert(group.to_string(), position);
+        }
+    }
+
+    /// Variable, in die die Gruppe ihre Ordner schreibt.
+    pub fn target_var(&self, group: &str) -> String {
+        self.target_vars.get(group).cloned().unwrap_or_else(|| logic::PATH_VAR.to_string())
+    }
+
+    /// Setzt die Variable einer Gruppe. PATH (oder leer) wird nicht gespeichert.
+    pub fn set_target_var(&mut self, group: &str, var: &str) {
+        let var = var.trim();
+        if var.is_empty() || logic::same_var(var, logic::PATH_VAR) {
+            self.target_vars.remove(group);
+        } else {
+            self.target_vars.insert(group.to_string(), var.to_string());
+        }
+    }
+
+    /// Variable und Einfügeposition einer Gruppe.
+    pub fn group_target(&self, group: &str) -> GroupTarget {
+        GroupTarget { var: self.target_var(group), position: self.insert_position(group) }
+    }
+
+    /// `group_target` für alle Gruppen.
+    pub fn group_targets(&self) -> HashMap<String, GroupTarget> {
+        self.languages.keys().map(|group| (group.clone(), self.group_target(group))).collect()
+    }
+
+    /// Alle Listen-Variablen, die von Gruppen verwaltet werden, PATH zuerst.
+    pub fn managed_vars(&self) -> Vec<String> {
+        let mut vars = vec![logic::PATH_VAR.to_string()];
+        let mut others: Vec<&String> = self.target_vars.values()
+            .chain(self.languages.values().flatten().flat_map(|v| v.lists.keys()))
+            .collect();
+        others.sort();
+        for var in others {
+            if !vars.iter().any(|v| logic::same_var(v, var)) {
+                vars.push(var.clone());
+            }
+        }
+        vars
+    }
+
+    /// Setzt (oder entfernt) die Abfrage einer Gruppe und verwirft deren alte Ergebnisse.
+    pub fn set_probe(&mut self, group: &str, probe: Option<Probe>) {
+        if let Some(versions) = self.languages.get(group) {
+            for v in versions {
+                self.probe_cache.remove(&v.path);
+            }
+        }
+        match probe {
+            Some(p) => self.probes.insert(group.to_string(), p),
+            None => self.probes.remove(group),
+        };
+    }
+
+    /// Setzt die Hooks einer Gruppe. Leere Hooks werden nicht gespeichert.
+    pub fn set_hooks(&mut self, group: &str, hooks: Hooks) {
+        if hooks.is_empty() {
+            self.hooks.remove(group);
+        } else {
+            self.hooks.insert(group.to_string(), hooks);
+        }
+    }
+
+    /// Ordner, deren Version (neu) abgefragt werden muss, mit der passenden Abfrage.
+    pub fn stale_probes(&self) -> Vec<(String, Probe)> {
+        let mut jobs = Vec::new();
+        for (group, probe) in &self.probes {
+            for v in self.languages.get(group).into_iter().flatten() {
+                let fresh = self.probe_cache.get(&v.path).is_some_and(|r| crate::probe::is_fresh(r, &v.path));
+                if !fresh && !jobs.iter().any(|(p, _): &(String, Probe)| p == &v.path) {
+                    jobs.push((v.path.clone(), probe.clone()));
+                }
+            }
+        }
+        jobs
+    }
+
+    /// Ersetzt alle Gruppen (z.B. nach einem Import) und korrigiert die ausgewählte Gruppe.
+    pub fn replace_groups(&mut self, data: HashMap<String, Vec<VersionEntry>>) {
+        self.languages = data;
+        if !self.languages.contains_key(&self.selected_group) {
+            if let Some(key) = self.languages.keys().next() {
+                self.selected_group = key.clone();
+            } else {
+                self.selected_group = "General".to_owned();
+            }
+        }
+    }
+
+    /// Lädt die Einstellungen aus der Datei, die auch die GUI benutzt.
+    ///
+    /// Ohne Datei gelten die Standardwerte. Eine beschädigte Datei ist ein Fehler, sonst würde
+    /// das nächste Speichern sie mit den Standardwerten überschreiben.
+    pub fn load() -> Result<Self, String> {
+        match config_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    fn load_from(path: &Path) -> Result<Self, String> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let store = read_store(path).map_err(|e| format!("{}: {}", path.display(), e))?;
+        match store.get(APP_KEY) {
+            Some(value) => ron::from_str(value).map_err(|e| format!("{}: {}", path.display(), e)),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Speichert die Einstellungen, ohne die übrigen Daten der GUI (Fenster etc.) zu verlieren.
+    pub fn save(&self) -> Result<(), String> {
+        self.save_to(&config_path().ok_or("No config directory found")?)
+    }
+
+    fn save_to(&self, path: &Path) -> Result<(), String> {
+        let mut store = read_store(path).unwrap_or_default();
+        store.insert(APP_KEY.to_string(), ron::to_string(self).map_err(|e| e.to_string())?);
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+        }
+        let file = File::create(path).map_err(|e| e.to_string())?;
+        ron::ser::to_writer_pretty(BufWriter::new(file), &store, Default::default()).map_err(|e| e.to_string())
+    }
+}
+
+fn read_store(path: &Path) -> Result<HashMap<String, String>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    ron::de::from_reader(BufReader::new(file)).map_err(|e| e.to_string())
+}
+
+/// Pfad zu `app.ron`, so wie ihn auch eframe verwendet.
+pub fn config_path() -> Option<PathBuf> {
+    data_dir().map(|d| d.join("app.ron"))
+}
+
+/// Ordner, in dem eframe (und damit dieses Tool) seine Daten ablegt.
+pub fn data_dir() -> Option<PathBuf> {
+    use std::env::var_os;
+
+    if cfg!(windows) {
+        var_os("APPDATA").map(|p| PathBuf::from(p).join(APP_ID).join("data"))
+    } else if cfg!(target_os = "macos") {
+        var_os("HOME").map(|p| {
+            PathBuf::from(p).join("Library").join("Application Support").join(APP_ID.replace(' ', "-"))
+        })
+    } else {
+        var_os("XDG_DATA_HOME")
+            .map(PathBuf::from)
+            .filter(|p| p.is_absolute())
+            .or_else(|| var_os("HOME").map(|p| PathBuf::from(p).join(".local").join("share")))
+            .map(|p| p.join(APP_ID.to_lowercase().replace(' ', "")))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_file_gives_the_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+
+        let config = Config::load_from(&dir.path().join("app.ron")).unwrap();
+
+        assert_eq!(config.selected_group, Config::default().selected_group);
+    }
+
+    #[test]
+    fn corrupt_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.ron");
+        std::fs::write(&path, "{\"app\": \"(languages: {\"}").unwrap();
+
+        assert!(Config::load_from(&path).is_err());
+
+        std::fs::write(&path, "not ron").unwrap();
+        assert!(Config::load_from(&path).is_err());
+    }
+
+    #[test]
+    fn saved_config_loads_again() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("app.ron");
+        let mut config = Config::default();
+        config.languages.insert("Go".to_string(), vec![VersionEntry { path: "/opt/go".to_string(), alias: "1.22".to_string(), ..Default::default() }]);
+        config.selected_group = "Go".to_string();
+
+        config.save_to(&path).unwrap();
+        let loaded = Config::load_from(&path).unwrap();
+
+        assert_eq!(loaded.selected_group, "Go");
+        assert_eq!(loaded.languages["Go"][0].path, "/opt/go");
+    }
+}
@@ -0,0 +1,254 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::logic;
+use crate::path_list;
+use crate::types::VersionEntry;
+
+/// Ein gefundener Ordner, der als Version hinzugefügt werden kann.
+#[derive(Clone, Debug)]
+pub struct Candidate {
+    pub group: String,
+    pub entry: VersionEntry,
+    pub selected: bool,
+}
+
+/// Woran eine Toolchain erkannt wird: eine ausführbare Datei im Ordner (oder in `bin/`).
+struct Layout {
+    group: &'static str,
+    executable: &'static str,
+    /// Variable, die auf den Installationsordner (nicht `bin/`) zeigen soll.
+    home_var: Option<&'static str>,
+    /// Unterordner des Installationsordners, die (falls vorhanden) mit aktiviert werden.
+    extra_dirs: &'static [&'static str],
+}
+
+const LAYOUTS: &[Layout] = &[
+    Layout { group: "Python", executable: "python", home_var: None, extra_dirs: &["Scripts"] },
+    Layout { group: "Python", executable: "python3", home_var: None, extra_dirs: &["Scripts"] },
+    Layout { group: "NodeJS", executable: "node", home_var: None, extra_dirs: &[] },
+    Layout { group: "Java", executable: "javac", home_var: Some("JAVA_HOME"), extra_dirs: &[] },
+    Layout { group: "Go", executable: "go", home_var: Some("GOROOT"), extra_dirs: &[] },
+    Layout { group: "Rust", executable: "rustc", home_var: None, extra_dirs: &[] },
+    Layout { group: "PHP", executable: "php", home_var: None, extra_dirs: &[] },
+    Layout { group: "Ruby", executable: "ruby", home_var: None, extra_dirs: &[] },
+];
+
+/// Bekannte Installationsorte auf diesem System (nur die, die existieren).
+///
+/// Ganze Laufwerke gehören nicht dazu, die Suche läuft im UI-Thread. Wer z.B. unter `C:\`
+/// installiert, fügt den Ordner selbst als Suchort hinzu.
+pub fn well_known_roots() -> Vec<PathBuf> {
+    let mut roots: Vec<PathBuf> = Vec::new();
+
+    #[cfg(windows)]
+    {
+        for var in ["ProgramFiles", "ProgramFiles(x86)"] {
+            if let Some(pf) = std::env::var_os(var).map(PathBuf::from) {
+                roots.push(pf.clone());
+                roots.push(pf.join("Java"));
+                roots.push(pf.join("Eclipse Adoptium"));
+            }
+        }
+        if let Some(local) = std::env::var_os("LOCALAPPDATA").map(PathBuf::from) {
+            roots.push(local.join("Programs").join("Python"));
+        }
+        if let Some(appdata) = std::env::var_os("APPDATA").map(PathBuf::from) {
+            roots.push(appdata.join("nvm"));
+        }
+    }
+    #[cfg(not(windows))]
+    {
+        for dir in ["/usr/lib/jvm", "/usr/local/go", "/usr/local/opt", "/opt"] {
+            roots.push(PathBuf::from(dir));
+        }
+        if let Some(home) = std::env::var_os("HOME").map(PathBuf::from) {
+            roots.push(home.join(".pyenv").join("versions"));
+            roots.push(home.join(".nvm").join("versions").join("node"));
+            roots.push(home.join(".rustup").join("toolchains"));
+            roots.push(home.join(".sdkman").join("candidates").join("java"));
+            roots.push(home.join("sdk"));
+        }
+    }
+
+    roots.retain(|r| r.is_dir());
+    roots
+}
+
+/// Durchsucht die Wurzelordner (und deren Unterordner bis Tiefe 2) nach Toolchains.
+/// Ordner, die schon in einer Gruppe stehen, werden übersprungen.
+pub fn discover(roots: &[PathBuf], languages: &HashMap<String, Vec<VersionEntry>>) -> Vec<Candidate> {
+    let mut found: BTreeMap<String, Candidate> = BTreeMap::new();
+
+    for root in roots {
+        for dir in dirs_up_to_depth(root, 2) {
+            for candidate in recognize(&dir, languages) {
+                let key = path_list::normalize(&candidate.entry.path);
+                let known = languages.values().flatten().any(|v| path_list::same_entry(&v.path, &candidate.entry.path));
+                if !known {
+                    found.entry(key).or_insert(candidate);
+                }
+            }
+        }
+    }
+
+    found.into_values().collect()
+}
+
+fn dirs_up_to_depth(root: &Path, depth: usize) -> Vec<PathBuf> {
+    let mut dirs = vec![root.to_path_buf()];
+    if depth == 0 {
+        return dirs;
+    }
+    if let Ok(entries) = fs::read_dir(root) {
+        for e in entries.filter_map(|e| e.ok()) {
+            let path = e.path();
+            if path.is_dir() {
+                dirs.extend(dirs_up_to_depth(&path, depth - 1));
+            }
+        }
+    }
+    dirs
+}
+
+fn recognize(dir: &Path, languages: &HashMap<String, Vec<VersionEntry>>) -> Vec<Candidate> {
+    let mut result = Vec::new();
+
+    for layout in LAYOUTS {
+        // Unter Windows liegt z.B. python.exe direkt im Ordner, sonst meist in bin/
+        for bin_dir in [dir.to_path_buf(), dir.join("bin")] {
+            if !has_executable(&bin_dir, layout.executable) {
+                continue;
+            }
+            let mut entry = VersionEntry {
+                path: bin_dir.display().to_string(),
+                alias: suggest_alias(dir),
+                ..Default::default()
+            };
+            // z.B. `Scripts` mit pip unter Windows
+            for extra in layout.extra_dirs.iter().map(|d| dir.join(d)).filter(|d| d.is_dir() && *d != bin_dir) {
+                entry.extra_paths.push(extra.display().to_string());
+            }
+            if let Some(var) = layout.home_var {
+                entry.env.insert(var.to_string(), dir.display().to_string());
+            }
+            let group = languages.keys()
+                .find(|k| k.eq_ignore_ascii_case(layout.group))
+                .cloned()
+                .unwrap_or_else(|| layout.group.to_string());
+            if !result.iter().any(|c: &Candidate| c.entry.path == entry.path) {
+                result.push(Candidate { group, entry, selected: true });
+            }
+            break;
+        }
+    }
+    result
+}
+
+fn has_executable(dir: &Path, name: &str) -> bool {
+    if cfg!(windows) {
+        ["exe", "cmd", "bat"].iter().any(|ext| logic::is_executable(&dir.join(format!("{}.{}", name, ext))))
+    } else {
+        logic::is_executable(&dir.join(name))
+    }
+}
+
+/// Schlägt einen Alias aus dem Ordnernamen vor: `jdk-17.0.2` wird zu `17.0.2`.
+fn suggest_alias(dir: &Path) -> String {
+    let name = dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    logic::extract_version(&name).unwrap_or(name)
+}
+
+/// Fügt die ausgewählten Kandidaten den Gruppen hinzu (legt fehlende Gruppen an).
+/// Liefert die Anzahl hinzugefügter Versionen.
+pub fn add_selected(languages: &mut HashMap<String, Vec<VersionEntry>>, candidates: &[Candidate]) -> usize {
+    let mut count = 0;
+    for c in candidates.iter().filter(|c| c.selected) {
+        let versions = languages.entry(c.group.clone()).or_default();
+        if !versions.iter().any(|v| path_list::same_entry(&v.path, &c.entry.path)) {
+            versions.push(c.entry.clone());
+            count += 1;
+        }
+    }
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Legt eine (für das System) ausführbare Datei `name` in `dir` an.
+    fn executable(dir: &Path, name: &str) {
+        fs::create_dir_all(dir).unwrap();
+        #[cfg(windows)]
+        fs::write(dir.join(format!("{}.exe", name)), "").unwrap();
+        #[cfg(not(windows))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            let path = dir.join(name);
+            fs::write(&path, "#!/bin/sh\n").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+    }
+
+    fn find<'a>(candidates: &'a [Candidate], group: &str) -> &'a Candidate {
+        candidates.iter().find(|c| c.group == group).unwrap()
+    }
+
+    #[test]
+    fn recognizes_layouts_below_the_roots() {
+        let root = tempfile::tempdir().unwrap();
+        let jdk = root.path().join("java").join("jdk-17.0.2");
+        executable(&jdk.join("bin"), "javac");
+        let python = root.path().join("Python311");
+        executable(&python, "python");
+        fs::create_dir(python.join("Scripts")).unwrap();
+        executable(&root.path().join("node-v20.1.0").join("bin"), "node");
+        // Zu tief für die Suche
+        executable(&root.path().join("a").join("b").join("c").join("bin"), "go");
+
+        let candidates = discover(&[root.path().to_path_buf()], &HashMap::new());
+
+        assert_eq!(candidates.len(), 3);
+        let java = find(&candidates, "Java");
+        assert_eq!(java.entry.path, jdk.join("bin").display().to_string());
+        assert_eq!(java.entry.alias, "17.0.2");
+        assert_eq!(java.entry.env.get("JAVA_HOME"), Some(&jdk.display().to_string()));
+        let python_candidate = find(&candidates, "Python");
+        assert_eq!(python_candidate.entry.path, python.display().to_string());
+        assert_eq!(python_candidate.entry.extra_paths, [python.join("Scripts").display().to_string()]);
+        assert_eq!(find(&candidates, "NodeJS").entry.alias, "20.1.0");
+    }
+
+    #[test]
+    fn skips_known_versions_and_reports_each_folder_once() {
+        let root = tempfile::tempdir().unwrap();
+        let node = root.path().join("node-v20.1.0");
+        executable(&node.join("bin"), "node");
+        let go = root.path().join("go1.22");
+        executable(&go.join("bin"), "go");
+        let known = VersionEntry { path: format!("{}/", go.join("bin").display()), ..Default::default() };
+        // Mit Schrägstrich am Ende schon in der Gruppe
+        let languages = HashMap::from([("go".to_string(), vec![known])]);
+
+        // Über beide Wurzeln erreichbar
+        let candidates = discover(&[root.path().to_path_buf(), node.clone()], &languages);
+
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].group, "NodeJS");
+        assert_eq!(candidates[0].entry.path, node.join("bin").display().to_string());
+    }
+
+    #[test]
+    fn uses_the_spelling_of_an_existing_group() {
+        let root = tempfile::tempdir().unwrap();
+        executable(&root.path().join("ruby-3.3").join("bin"), "ruby");
+        let languages = HashMap::from([("ruby".to_string(), Vec::new())]);
+
+        let candidates = discover(&[root.path().to_path_buf()], &languages);
+
+        assert_eq!(candidates[0].group, "ruby");
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::{BTreeMap, BTreeSet};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::PathBuf;
+
+use crate::backend::EnvBackend;
+use crate::types::VersionEntry;
+
+/// Platzhalter für den Ordner der Version in Variablenwerten.
+pub const PATH_PLACEHOLDER: &str = "${path}";
+
+const BACKUP_FILE: &str = "env_backup.json";
+
+/// Originalwerte der Variablen, bevor das Tool sie zum ersten Mal gesetzt hat.
+/// `None` bedeutet, dass die Variable vorher nicht existierte.
+type Backup = BTreeMap<String, Option<String>>;
+
+fn backup_path(backend: &dyn EnvBackend) -> Option<PathBuf> {
+    backend.data_dir().map(|d| d.join(BACKUP_FILE))
+}
+
+fn load_backup(backend: &dyn EnvBackend) -> Backup {
+    backup_path(backend)
+        .and_then(|p| File::open(p).ok())
+        .and_then(|f| serde_json::from_reader(BufReader::new(f)).ok())
+        .unwrap_or_default()
+}
+
+/// Ohne Ordner (z.B. Trockenlauf im Speicher) wird die Sicherung nicht aufbewahrt.
+fn save_backup(backend: &dyn EnvBackend, backup: &Backup) -> Result<(), String> {
+    let Some(path) = backup_path(backend) else { return Ok(()) };
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(BufWriter::new(file), backup).map_err(|e| e.to_string())
+}
+
+/// Ersetzt `${path}` durch den Hauptordner der Version.
+pub fn expand_value(value: &str, entry: &VersionEntry) -> String {
+    value.replace(PATH_PLACEHOLDER, &entry.path)
+}
+
+/// Was das Aktivieren an den Variablen der Versionen ändert. Geschrieben wird erst
+/// zusammen mit den Listen (siehe `logic::write_activation`).
+#[derive(Default)]
+pub struct EnvPlan {
+    /// Neuer Wert je Variable, `None` löscht sie.
+    changes: BTreeMap<String, Option<String>>,
+    /// Sicherung vor und nach dem Aktivieren.
+    old_backup: Backup,
+    backup: Backup,
+}
+
+impl EnvPlan {
+    /// Beginnt mit der gespeicherten Sicherung des Backends.
+    pub fn new(backend: &dyn EnvBackend) -> Self {
+        let backup = load_backup(backend);
+        Self { changes: BTreeMap::new(), old_backup: backup.clone(), backup }
+    }
+
+    /// Plant die Variablen von `entry`. Variablen, die nur andere Versionen der Gruppe setzen,
+    /// bekommen ihren ursprünglichen Wert zurück (oder werden gelöscht, wenn es keinen gab).
+    pub fn add(&mut self, backend: &dyn EnvBackend, group_versions: &[VersionEntry], entry: &VersionEntry) {
+        let group_names: BTreeSet<&String> = group_versions.iter().flat_map(|v| v.env.keys()).collect();
+
+        for name in group_names.iter().filter(|n| !entry.env.contains_key(n.as_str())) {
+            // Nie von uns gesetzt, also auch nichts zurückzusetzen
+            if let Some(original) = self.backup.remove(name.as_str()) {
+                self.changes.insert(name.to_string(), original);
+            }
+        }
+
+        for (name, value) in &entry.env {
+            if !self.backup.contains_key(name) {
+                self.backup.insert(name.clone(), backend.read_var(name));
+            }
+            self.changes.insert(name.clone(), Some(expand_value(value, entry)));
+        }
+    }
+
+    /// Die geplanten Schreibvorgänge (`None` löscht die Variable).
+    pub fn writes(&self) -> impl Iterator<Item = (String, Option<String>)> + '_ {
+        self.changes.iter().map(|(name, value)| (name.clone(), value.clone()))
+    }
+
+    /// Speichert die Sicherung, wie sie nach dem Aktivieren gilt. Muss vor dem ersten
+    /// Schreiben passieren, sonst gingen die Originalwerte bei einem Absturz verloren.
+    pub fn save_backup(&self, backend: &dyn EnvBackend) -> Result<(), String> {
+        if self.backup == self.old_backup {
+            return Ok(());
+        }
+        save_backup(backend, &self.backup)
+    }
+
+    /// Stellt die Sicherung von vorher wieder her, wenn das Aktivieren fehlschlägt.
+    pub fn restore_backup(&self, backend: &dyn EnvBackend) -> Result<(), String> {
+        if self.backup == self.old_backup {
+            return Ok(());
+        }
+        save_backup(backend, &self.old_backup)
+    }
+}
+
+/// Liest `NAME=Wert` Zeilen (wie im Bearbeiten-Feld). Leere Zeilen werden ignoriert.
+pub fn parse_lines(text: &str) -> BTreeMap<String, String> {
+    text.lines()
+        .filter_map(|l| l.split_once('='))
+        .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+        .filter(|(k, _)| !k.is_empty())
+        .collect()
+}
+
+/// Gegenstück zu [`parse_lines`].
+pub fn format_lines(env: &BTreeMap<String, String>) -> String {
+    env.iter().map(|(k, v)| format!("{}={}", k, v)).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::{FileBackend, MemoryBackend};
+    use crate::logic;
+    use crate::types::{GroupTarget, SwitchMode};
+
+    fn jdk(path: &str, alias: &str, env: &[(&str, &str)]) -> VersionEntry {
+        VersionEntry {
+            path: path.to_string(),
+            alias: alias.to_string(),
+            env: env.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect(),
+            ..Default::default()
+        }
+    }
+
+    fn switch(backend: &dyn EnvBackend, versions: &[VersionEntry], idx: usize) {
+        logic::activate(backend, SwitchMode::Path, "java", versions, &versions[idx], &GroupTarget::default()).unwrap();
+    }
+
+    #[test]
+    fn plan_expands_the_path_placeholder() {
+        let backend = MemoryBackend::default();
+        let versions = [jdk("/opt/jdk17", "17", &[("JAVA_HOME", "${path}"), ("JAVA_OPTS", "-Dhome=${path}/conf")])];
+
+        let mut plan = EnvPlan::new(&backend);
+        plan.add(&backend, &versions, &versions[0]);
+
+        let writes: Vec<_> = plan.writes().collect();
+        assert_eq!(writes, [
+            ("JAVA_HOME".to_string(), Some("/opt/jdk17".to_string())),
+            ("JAVA_OPTS".to_string(), Some("-Dhome=/opt/jdk17/conf".to_string())),
+        ]);
+    }
+
+    #[test]
+    fn switching_sets_and_restores_the_original_value() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FileBackend::new(dir.path().join("env.json"));
+        backend.write_var("JAVA_HOME", "/usr/lib/jvm/default").unwrap();
+        let versions = [jdk("/opt/jdk17", "17", &[("JAVA_HOME", "${path}")]), jdk("/opt/jdk8", "8", &[])];
+
+        switch(&backend, &versions, 0);
+        assert_eq!(backend.read_var("JAVA_HOME").as_deref(), Some("/opt/jdk17"));
+
+        switch(&backend, &versions, 1);
+        assert_eq!(backend.read_var("JAVA_HOME").as_deref(), Some("/usr/lib/jvm/default"));
+    }
+
+    #[test]
+    fn switching_away_removes_a_variable_that_did_not_exist() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FileBackend::new(dir.path().join("env.json"));
+        let versions = [jdk("/opt/jdk17", "17", &[("JAVA_HOME", "${path}")]), jdk("/opt/jdk8", "8", &[])];
+
+        switch(&backend, &versions, 0);
+        assert_eq!(backend.read_var("JAVA_HOME").as_deref(), Some("/opt/jdk17"));
+
+        switch(&backend, &versions, 1);
+        assert_eq!(backend.read_var("JAVA_HOME"), None);
+        assert_eq!(load_backup(&backend), Backup::new());
+    }
+
+    #[test]
+    fn backup_keeps_the_first_original() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FileBackend::new(dir.path().join("env.json"));
+        backend.write_var("JAVA_HOME", "/usr/lib/jvm/default").unwrap();
+        let versions = [jdk("/opt/jdk17", "17", &[("JAVA_HOME", "${path}")]), jdk("/opt/jdk21", "21", &[("JAVA_HOME", "${path}")])];
+
+        switch(&backend, &versions, 0);
+        switch(&backend, &versions, 1);
+
+        assert_eq!(backend.read_var("JAVA_HOME").as_deref(), Some("/opt/jdk21"));
+        assert_eq!(load_backup(&backend), Backup::from([("JAVA_HOME".to_string(), Some("/usr/lib/jvm/default".to_string()))]));
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::HashMap;
+use std::process::{Command, Stdio};
+use std::sync::Arc;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::Duration;
+
+use crate::backend::EnvBackend;
+use crate::env_vars;
+use crate::layers;
+use crate::logic;
+use crate::path_list::{self, PathList};
+use crate::probe;
+use crate::types::{Hooks, VersionEntry};
+use crate::user_env;
+
+/// Hooks dürfen länger dauern als Versionsabfragen (z.B. `npm rebuild`).
+const HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+/// So viel Ausgabe landet höchstens im Verlauf.
+const MAX_OUTPUT: usize = 500;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HookPhase {
+    Pre,
+    Post,
+}
+
+impl HookPhase {
+    pub fn describe(&self) -> &'static str {
+        match self {
+            HookPhase::Pre => "pre",
+            HookPhase::Post => "post",
+        }
+    }
+}
+
+/// Ein ausgeführter Hook-Befehl.
+#[derive(Clone, Debug)]
+pub struct HookRun {
+    pub phase: HookPhase,
+    pub group: String,
+    pub command: String,
+    /// `None`, wenn der Befehl nicht starten konnte, zu lange lief oder durch ein Signal endete.
+    pub exit_code: Option<i32>,
+    /// stdout und stderr (oder der Grund, warum er nicht lief).
+    pub output: String,
+}
+
+impl HookRun {
+    pub fn succeeded(&self) -> bool {
+        self.exit_code == Some(0)
+    }
+
+    pub fn describe(&self) -> String {
+        let status = match self.exit_code {
+            Some(code) => format!("exit {}", code),
+            None => "failed".to_string(),
+        };
+        let mut text = format!("{}-hook {}: `{}` ({})", self.phase.describe(), self.group, self.command, status);
+        let output = self.output.trim();
+        if !output.is_empty() {
+            let mut short: String = output.chars().take(MAX_OUTPUT).collect();
+            if short.len() < output.len() {
+                short.push('…');
+            }
+            text = format!("{}: {}", text, short);
+        }
+        text
+    }
+}
+
+/// Befehle einer Phase: erst die der Gruppe, dann die der Version.
+pub fn commands<'a>(group_hooks: Option<&'a Hooks>, entry: &'a VersionEntry, phase: HookPhase) -> Vec<&'a str> {
+    let pick = |hooks: &'a Hooks| match phase {
+        HookPhase::Pre => &hooks.pre,
+        HookPhase::Post => &hooks.post,
+    };
+    group_hooks.map(pick).into_iter().chain(Some(pick(&entry.hooks))).flatten().map(String::as_str).collect()
+}
+
+/// Aktiviert über `switch` und führt die Hooks aller `versions` (Gruppe, Version) drumherum aus.
+///
+/// Schlägt ein Pre-Hook fehl, wird nicht umgeschaltet. Post-Hooks laufen nur nach erfolgreichem
+/// Umschalten, ihr Ergebnis ändert daran nichts mehr. Alle Läufe kommen zurück, auch bei Fehlern.
+pub fn with_hooks(
+    backend: &dyn EnvBackend,
+    group_hooks: &HashMap<String, Hooks>,
+    versions: &[(String, VersionEntry)],
+    switch: impl FnOnce() -> Result<(), String>,
+) -> (Vec<HookRun>, Result<(), String>) {
+    let mut runs = Vec::new();
+
+    for (group, entry) in versions {
+        let commands = commands(group_hooks.get(group), entry, HookPhase::Pre);
+        if commands.is_empty() {
+            continue;
+        }
+        let env = hook_env(backend, group, entry);
+        for command in commands {
+            let run = run_hook(HookPhase::Pre, group, entry, command, &env);
+            let failed = !run.succeeded();
+            runs.push(run);
+            if failed {
+                return (runs, Err(format!("Pre-activation hook of {} failed: {}", group, command)));
+            }
+        }
+    }
+
+    if let Err(e) = switch() {
+        return (runs, Err(e));
+    }
+
+    for (group, entry) in versions {
+        let commands = commands(group_hooks.get(group), entry, HookPhase::Post);
+        if commands.is_empty() {
+            continue;
+        }
+        // Nach dem Umschalten gelesen, also mit der neuen Umgebung
+        let env = hook_env(backend, group, entry);
+        for command in commands {
+            runs.push(run_hook(HookPhase::Post, group, entry, command, &env));
+        }
+    }
+    (runs, Ok(()))
+}
+
+/// Hook-Läufe und Ergebnis einer Umschaltung, wie von `with_hooks`.
+pub type SwitchOutcome = (Vec<HookRun>, Result<(), String>);
+
+/// Führt `with_hooks` in einem Hintergrund-Thread aus, damit lange Hooks die Oberfläche nicht
+/// blockieren. Das Ergebnis kommt einmal über den Kanal.
+pub fn spawn_with_hooks(
+    backend: Arc<dyn EnvBackend + Send + Sync>,
+    group_hooks: HashMap<String, Hooks>,
+    versions: Vec<(String, VersionEntry)>,
+    switch: impl FnOnce(&dyn EnvBackend) -> Result<(), String> + Send + 'static,
+) -> Receiver<SwitchOutcome> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        let backend: &dyn EnvBackend = backend.as_ref();
+        let _ = tx.send(with_hooks(backend, &group_hooks, &versions, || switch(backend)));
+    });
+    rx
+}
+
+/// Umgebung für einen Hook: die des Prozesses, darüber die Variablen des Benutzers aus dem
+/// Backend und der wirksame PATH. Dazu `VS_GROUP`, `VS_ALIAS` und `VS_PATH` für die Version.
+fn hook_env(backend: &dyn EnvBackend, group: &str, entry: &VersionEntry) -> Vec<(String, String)> {
+    let mut env: Vec<(String, String)> = user_env::list(backend).into_iter()
+        .filter(|v| !logic::same_var(&v.name, logic::PATH_VAR))
+        .map(|v| {
+            let value = if v.expandable { path_list::expand(&v.value) } else { v.value };
+            (v.name, value)
+        })
+        .collect();
+
+    let path: PathList = layers::current_effective(backend).iter().map(|e| path_list::expand(&e.entry)).collect();
+    env.push(("PATH".to_string(), path.to_string()));
+    env.push(("VS_GROUP".to_string(), group.to_string()));
+    env.push(("VS_ALIAS".to_string(), entry.alias.clone()));
+    env.push(("VS_PATH".to_string(), path_list::expand(&entry.path)));
+    env
+}
+
+fn run_hook(phase: HookPhase, group: &str, entry: &VersionEntry, command: &str, env: &[(String, String)]) -> HookRun {
+    let line = env_vars::expand_value(command, entry);
+    let mut cmd = if cfg!(windows) {
+        let mut c = Command::new("cmd");
+        c.arg("/C").arg(&line);
+        c
+    } else {
+        let mut c = Command::new("sh");
+        c.arg("-c").arg(&line);
+        c
+    };
+    cmd.envs(env.iter().map(|(k, v)| (k, v)))
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(windows)]
+    {
+        // Kein Konsolenfenster aufblitzen lassen
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000);
+    }
+
+    let (exit_code, output) = match probe::run_with_timeout(cmd, HOOK_TIMEOUT) {
+        Ok((status, output)) => (status.code(), output),
+        Err(e) => (None, e),
+    };
+    HookRun { phase, group: group.to_string(), command: line, exit_code, output }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryBackend;
+    use crate::types::{GroupTarget, SwitchMode};
+
+    fn version(path: &str, alias: &str, hooks: Hooks) -> VersionEntry {
+        VersionEntry { path: path.to_string(), alias: alias.to_string(), hooks, ..Default::default() }
+    }
+
+    fn switch_node(hooks: Hooks) -> (MemoryBackend, Vec<HookRun>, Result<(), String>) {
+        let backend = MemoryBackend::with_vars([(logic::PATH_VAR.to_string(), "/opt/node18".to_string())].into());
+        let versions = vec![version("/opt/node18", "18", Hooks::default()), version("/opt/node20", "20", hooks)];
+        let selected = vec![("node".to_string(), versions[1].clone())];
+
+        let (runs, result) = with_hooks(&backend, &HashMap::new(), &selected, || {
+            logic::activate(&backend, SwitchMode::Path, "node", &versions, &versions[1], &GroupTarget::default())
+        });
+        (backend, runs, result)
+    }
+
+    // Die Befehle laufen in `sh -c` bzw. `cmd /C`, `exit 3` versteht beides
+    #[test]
+    fn failing_pre_hook_leaves_path_unchanged() {
+        let hooks = Hooks { pre: vec!["exit 3".to_string()], post: vec!["exit 0".to_string()] };
+
+        let (backend, runs, result) = switch_node(hooks);
+
+        assert!(result.is_err());
+        assert_eq!(runs.len(), 1);
+        assert_eq!(runs[0].phase, HookPhase::Pre);
+        assert_eq!(runs[0].exit_code, Some(3));
+        assert_eq!(logic::get_current_path_var(&backend), "/opt/node18");
+    }
+
+    #[test]
+    fn post_hooks_run_after_switching() {
+        let hooks = Hooks { pre: vec!["exit 0".to_string()], post: vec!["exit 0".to_string()] };
+
+        let (backend, runs, result) = switch_node(hooks);
+
+        assert!(result.is_ok());
+        assert_eq!(runs.iter().map(|r| r.phase).collect::<Vec<_>>(), [HookPhase::Pre, HookPhase::Post]);
+        assert!(runs.iter().all(HookRun::succeeded));
+        assert_eq!(logic::get_current_path_var(&backend), "/opt/node20");
+    }
+
+    #[test]
+    fn background_switch_reports_back_once() {
+        let backend: Arc<dyn EnvBackend + Send + Sync> =
+            Arc::new(MemoryBackend::with_vars([(logic::PATH_VAR.to_string(), "/opt/node18".to_string())].into()));
+        let hooks = Hooks { pre: vec![], post: vec!["exit 0".to_string()] };
+        let versions = vec![version("/opt/node18", "18", Hooks::default()), version("/opt/node20", "20", hooks)];
+        let selected = vec![("node".to_string(), versions[1].clone())];
+
+        let rx = spawn_with_hooks(backend.clone(), HashMap::new(), selected, move |backend| {
+            logic::activate(backend, SwitchMode::Path, "node", &versions, &versions[1], &GroupTarget::default())
+        });
+
+        let (runs, result) = rx.recv().unwrap();
+        assert!(result.is_ok());
+        assert_eq!(runs.len(), 1);
+        assert!(rx.recv().is_err());
+        assert_eq!(logic::get_current_path_var(backend.as_ref()), "/opt/node20");
+    }
+}
@@ -1,5 +1,9 @@
 use serde::{Deserialize, Serialize};
 
+use crate::audit::Severity;
+use crate::layers::PathLayer;
+use crate::types::{CleanerFix, InsertPosition, IssueType};
+
 #[derive(Deserialize, Serialize, PartialEq, Clone, Copy, Debug)]
 pub enum Language {
     English,
@@ -284,8 +288,8 @@ impl Language {
 
     pub fn status_cleaned(&self, count: usize) -> String {
         match self {
-            Language::English => format!("Removed {} entries.", count),
-            Language::German => format!("{} Einträge entfernt.", count),
+            Language::English => format!("Fixed {} entries.", count),
+            Language::German => format!("{} Einträge bereinigt.", count),
         }
     }
 
@@ -337,4 +341,677 @@ impl Language {
             Language::German => "Noch keine Aktivitäten aufgezeichnet.",
         }
     }
-}
\ No newline at end of file
+
+    pub fn tooltip_project(&self) -> &str {
+        match self {
+            Language::English => "Activate project versions (.versionswitcher.toml, .tool-versions, ...)",
+            Language::German => "Projekt-Versionen aktivieren (.versionswitcher.toml, .tool-versions, ...)",
+        }
+    }
+
+    pub fn status_no_pins(&self) -> &str {
+        match self {
+            Language::English => "No version files found in this folder or above.",
+            Language::German => "Keine Versionsdateien in diesem Ordner oder darüber gefunden.",
+        }
+    }
+
+    pub fn status_project_activated(&self, count: usize) -> String {
+        match self {
+            Language::English => format!("Activated {} project versions.", count),
+            Language::German => format!("{} Projekt-Versionen aktiviert.", count),
+        }
+    }
+
+    pub fn label_shim_mode(&self) -> &str {
+        match self {
+            Language::English => "Shim mode",
+            Language::German => "Shim-Modus",
+        }
+    }
+
+    pub fn tooltip_shim_mode(&self) -> &str {
+        match self {
+            Language::English => "Put one shim folder on PATH and only rewrite the launchers in it. Open terminals see the switch immediately.",
+            Language::German => "Einen Shim-Ordner in den PATH setzen und nur die Starter darin neu schreiben. Offene Terminals sehen den Wechsel sofort.",
+        }
+    }
+
+    pub fn label_env_vars(&self) -> &str {
+        match self {
+            Language::English => "Environment variables (NAME=value, ${path} = version folder):",
+            Language::German => "Umgebungsvariablen (NAME=Wert, ${path} = Ordner der Version):",
+        }
+    }
+
+    pub fn tooltip_discover(&self) -> &str {
+        match self {
+            Language::English => "Discover installed versions",
+            Language::German => "Installierte Versionen suchen",
+        }
+    }
+
+    pub fn window_discover_title(&self) -> &str {
+        match self {
+            Language::English => "Discover Versions",
+            Language::German => "Versionen suchen",
+        }
+    }
+
+    pub fn label_discover_roots(&self) -> &str {
+        match self {
+            Language::English => "Additional folders (well-known locations are always searched):",
+            Language::German => "Zusätzliche Ordner (bekannte Orte werden immer durchsucht):",
+        }
+    }
+
+    pub fn btn_add_root(&self) -> &str {
+        match self {
+            Language::English => "Add folder",
+            Language::German => "Ordner hinzufügen",
+        }
+    }
+
+    pub fn label_no_candidates(&self) -> &str {
+        match self {
+            Language::English => "No new versions found.",
+            Language::German => "Keine neuen Versionen gefunden.",
+        }
+    }
+
+    pub fn btn_add_selected(&self) -> &str {
+        match self {
+            Language::English => "Add Selected",
+            Language::German => "Ausgewählte hinzufügen",
+        }
+    }
+
+    pub fn status_discovered(&self, count: usize) -> String {
+        match self {
+            Language::English => format!("Added {} discovered versions.", count),
+            Language::German => format!("{} gefundene Versionen hinzugefügt.", count),
+        }
+    }
+
+    pub fn tooltip_probe(&self) -> &str {
+        match self {
+            Language::English => "Detect the real version of each entry",
+            Language::German => "Echte Version jedes Eintrags erkennen",
+        }
+    }
+
+    pub fn window_probe_title(&self) -> &str {
+        match self {
+            Language::English => "Version Detection",
+            Language::German => "Versionserkennung",
+        }
+    }
+
+    pub fn label_probe_executable(&self) -> &str {
+        match self {
+            Language::English => "Program (without extension):",
+            Language::German => "Programm (ohne Endung):",
+        }
+    }
+
+    pub fn label_probe_args(&self) -> &str {
+        match self {
+            Language::English => "Arguments:",
+            Language::German => "Argumente:",
+        }
+    }
+
+    pub fn label_probe_regex(&self) -> &str {
+        match self {
+            Language::English => "Regex (first group = version):",
+            Language::German => "Regex (erste Gruppe = Version):",
+        }
+    }
+
+    pub fn btn_remove_probe(&self) -> &str {
+        match self {
+            Language::English => "Remove",
+            Language::German => "Entfernen",
+        }
+    }
+
+    pub fn tooltip_probe_mismatch(&self, detected: &str) -> String {
+        match self {
+            Language::English => format!("The folder actually contains version {}.", detected),
+            Language::German => format!("Der Ordner enthält tatsächlich Version {}.", detected),
+        }
+    }
+
+    pub fn label_probing(&self) -> &str {
+        match self {
+            Language::English => "detecting...",
+            Language::German => "wird erkannt...",
+        }
+    }
+
+    pub fn tooltip_snapshots(&self) -> &str {
+        match self {
+            Language::English => "PATH snapshots (undo changes)",
+            Language::German => "PATH-Sicherungen (Änderungen rückgängig machen)",
+        }
+    }
+
+    pub fn window_snapshots_title(&self) -> &str {
+        match self {
+            Language::English => "PATH Snapshots",
+            Language::German => "PATH-Sicherungen",
+        }
+    }
+
+    pub fn label_no_snapshots(&self) -> &str {
+        match self {
+            Language::English => "No snapshots yet. One is saved before every PATH change.",
+            Language::German => "Noch keine Sicherungen. Vor jeder PATH-Änderung wird eine angelegt.",
+        }
+    }
+
+    pub fn label_snapshot_diff(&self) -> &str {
+        match self {
+            Language::English => "Changes compared to the current PATH when restoring:",
+            Language::German => "Änderungen gegenüber dem aktuellen PATH beim Zurückholen:",
+        }
+    }
+
+    pub fn btn_restore(&self) -> &str {
+        match self {
+            Language::English => "Restore",
+            Language::German => "Wiederherstellen",
+        }
+    }
+
+    pub fn status_restored(&self, var: &str, time: &str) -> String {
+        match self {
+            Language::English => format!("{} restored from {}.", var, time),
+            Language::German => format!("{} vom {} wiederhergestellt.", var, time),
+        }
+    }
+
+    pub fn label_confirm_changes(&self) -> &str {
+        match self {
+            Language::English => "Preview PATH changes",
+            Language::German => "PATH-Änderungen vorher anzeigen",
+        }
+    }
+
+    pub fn tooltip_confirm_changes(&self) -> &str {
+        match self {
+            Language::English => "Show the new PATH and any warnings before switching or cleaning, and only write after confirmation.",
+            Language::German => "Vor dem Wechseln oder Bereinigen den neuen PATH samt Warnungen zeigen und erst nach Bestätigung schreiben.",
+        }
+    }
+
+    pub fn window_preview_title(&self) -> &str {
+        match self {
+            Language::English => "Preview",
+            Language::German => "Vorschau",
+        }
+    }
+
+    pub fn label_var_unchanged(&self, var: &str) -> String {
+        match self {
+            Language::English => format!("The {} does not change.", var),
+            Language::German => format!("Der {} ändert sich nicht.", var),
+        }
+    }
+
+    pub fn btn_apply(&self) -> &str {
+        match self {
+            Language::English => "Apply",
+            Language::German => "Übernehmen",
+        }
+    }
+
+    pub fn layer_name(&self, layer: PathLayer) -> &str {
+        match (self, layer) {
+            (_, PathLayer::Machine) => "System",
+            (Language::English, PathLayer::User) => "User",
+            (Language::German, PathLayer::User) => "Benutzer",
+            (Language::English, PathLayer::Session) => "Session",
+            (Language::German, PathLayer::Session) => "Sitzung",
+        }
+    }
+
+    pub fn header_effective_path(&self) -> &str {
+        match self {
+            Language::English => "Effective PATH (System, User, Session)",
+            Language::German => "Wirksamer PATH (System, Benutzer, Sitzung)",
+        }
+    }
+
+    pub fn tooltip_shadowed(&self, details: &str) -> String {
+        match self {
+            Language::English => format!("Active, but hidden by an earlier PATH entry:\n{}", details),
+            Language::German => format!("Aktiv, aber von einem früheren PATH-Eintrag verdeckt:\n{}", details),
+        }
+    }
+
+    pub fn label_which(&self) -> &str {
+        match self {
+            Language::English => "Which command:",
+            Language::German => "Welcher Befehl:",
+        }
+    }
+
+    pub fn label_which_none(&self) -> &str {
+        match self {
+            Language::English => "Not found in PATH.",
+            Language::German => "Nicht im PATH gefunden.",
+        }
+    }
+
+    pub fn tooltip_which_winner(&self) -> &str {
+        match self {
+            Language::English => "This one is started by a new terminal",
+            Language::German => "Diese Datei startet ein neues Terminal",
+        }
+    }
+
+    pub fn issue_shadowed(&self) -> &str {
+        match self {
+            Language::English => "Shadowed",
+            Language::German => "Verdeckt",
+        }
+    }
+
+    pub fn detail_shadowed(&self, group: &str, by: &str, names: &[String]) -> String {
+        match self {
+            Language::English => format!("{} from {} hidden by {}", names.join(", "), group, by),
+            Language::German => format!("{} aus {} von {} verdeckt", names.join(", "), group, by),
+        }
+    }
+
+    pub fn tooltip_shadowed_issue(&self) -> &str {
+        match self {
+            Language::English => "Both groups are active and offer the same programs. Which one wins depends on the activation order. Not changed by the cleaner.",
+            Language::German => "Beide Gruppen sind aktiv und bieten dieselben Programme an. Welche gewinnt, hängt von der Reihenfolge beim Aktivieren ab. Der Cleaner ändert daran nichts.",
+        }
+    }
+
+    /// Kurzname eines Problems für die Liste im Cleaner.
+    pub fn issue_label(&self, issue: &IssueType) -> &str {
+        match (self, issue) {
+            (_, IssueType::Missing) => self.issue_missing(),
+            (_, IssueType::Duplicate) => self.issue_duplicate(),
+            (_, IssueType::Shadowed { .. }) => self.issue_shadowed(),
+            (Language::English, IssueType::Variant { .. }) => "Variant",
+            (Language::German, IssueType::Variant { .. }) => "Schreibweise",
+            (Language::English, IssueType::NotADirectory { .. }) => "File",
+            (Language::German, IssueType::NotADirectory { .. }) => "Datei",
+            (Language::English, IssueType::EmptyDir) => "Empty",
+            (Language::German, IssueType::EmptyDir) => "Leer",
+            (Language::English, IssueType::NoExecutables) => "No programs",
+            (Language::German, IssueType::NoExecutables) => "Keine Programme",
+            (Language::English, IssueType::UnresolvedVar { .. }) => "Unknown variable",
+            (Language::German, IssueType::UnresolvedVar { .. }) => "Unbekannte Variable",
+            (Language::English, IssueType::Relative) => "Relative",
+            (Language::German, IssueType::Relative) => "Relativ",
+            (Language::English, IssueType::Incomplete { .. }) => "Incomplete",
+            (Language::German, IssueType::Incomplete { .. }) => "Unvollständig",
+        }
+    }
+
+    /// Zusätzliche Angaben zu einem Problem, falls es welche gibt.
+    pub fn issue_detail(&self, issue: &IssueType) -> Option<String> {
+        match (self, issue) {
+            (_, IssueType::Shadowed { group, by, names }) => Some(self.detail_shadowed(group, by, names)),
+            (Language::English, IssueType::Variant { of }) => Some(format!("same as {}", of)),
+            (Language::German, IssueType::Variant { of }) => Some(format!("wie {}", of)),
+            (_, IssueType::UnresolvedVar { names }) => Some(names.join(", ")),
+            (Language::English, IssueType::Relative) => Some("depends on the current directory".to_string()),
+            (Language::German, IssueType::Relative) => Some("hängt vom aktuellen Verzeichnis ab".to_string()),
+            (Language::English, IssueType::Incomplete { group, alias, .. }) => Some(format!("{} {} is only partly in PATH", group, alias)),
+            (Language::German, IssueType::Incomplete { group, alias, .. }) => Some(format!("{} {} steht nur teilweise im PATH", group, alias)),
+            _ => None,
+        }
+    }
+
+    pub fn fix_label(&self, fix: &CleanerFix) -> String {
+        match (self, fix) {
+            (Language::English, CleanerFix::Remove) => "remove".to_string(),
+            (Language::German, CleanerFix::Remove) => "entfernen".to_string(),
+            (Language::English, CleanerFix::KeepFirst) => "keep first entry".to_string(),
+            (Language::German, CleanerFix::KeepFirst) => "ersten Eintrag behalten".to_string(),
+            (Language::English, CleanerFix::Replace(with)) => format!("replace with {}", with),
+            (Language::German, CleanerFix::Replace(with)) => format!("ersetzen durch {}", with),
+            (Language::English, CleanerFix::InsertAfter(dirs)) => format!("add {} after it", dirs.join(", ")),
+            (Language::German, CleanerFix::InsertAfter(dirs)) => format!("{} dahinter einfügen", dirs.join(", ")),
+        }
+    }
+
+    pub fn label_cleanup_mode(&self) -> &str {
+        match self {
+            Language::English => "Cleanup",
+            Language::German => "Bereinigen",
+        }
+    }
+
+    pub fn label_audit_mode(&self) -> &str {
+        match self {
+            Language::English => "Security audit",
+            Language::German => "Sicherheitsprüfung",
+        }
+    }
+
+    pub fn severity_name(&self, severity: Severity) -> &str {
+        match (self, severity) {
+            (Language::English, Severity::Warning) => "Warning",
+            (Language::German, Severity::Warning) => "Warnung",
+            (Language::English, Severity::Critical) => "Critical",
+            (Language::German, Severity::Critical) => "Kritisch",
+        }
+    }
+
+    pub fn label_no_findings(&self) -> &str {
+        match self {
+            Language::English => "No risky entries found.",
+            Language::German => "Keine riskanten Einträge gefunden.",
+        }
+    }
+
+    pub fn btn_export_report(&self) -> &str {
+        match self {
+            Language::English => "💾 Export report",
+            Language::German => "💾 Bericht exportieren",
+        }
+    }
+
+    pub fn status_report_exported(&self) -> &str {
+        match self {
+            Language::English => "Audit report saved.",
+            Language::German => "Prüfbericht gespeichert.",
+        }
+    }
+
+    pub fn status_compacted(&self, duplicates: usize, saved: usize) -> String {
+        match self {
+            Language::English => format!("{} duplicates removed, {} characters saved.", duplicates, saved),
+            Language::German => format!("{} Duplikate entfernt, {} Zeichen gespart.", duplicates, saved),
+        }
+    }
+
+    pub fn label_path_length(&self, length: usize, limit: usize) -> String {
+        match self {
+            Language::English => format!("⚠ PATH is {} characters long (warning from {})", length, limit),
+            Language::German => format!("⚠ PATH ist {} Zeichen lang (Warnung ab {})", length, limit),
+        }
+    }
+
+    pub fn label_length_warning(&self) -> &str {
+        match self {
+            Language::English => "Warn from PATH length:",
+            Language::German => "Warnen ab PATH-Länge:",
+        }
+    }
+
+    pub fn btn_compact(&self) -> &str {
+        match self {
+            Language::English => "🗜 Compact",
+            Language::German => "🗜 Kürzen",
+        }
+    }
+
+    pub fn tooltip_compact(&self) -> &str {
+        match self {
+            Language::English => "Removes duplicates and replaces known folders with references like %LOCALAPPDATA%",
+            Language::German => "Entfernt Duplikate und ersetzt bekannte Ordner durch Verweise wie %LOCALAPPDATA%",
+        }
+    }
+
+    pub fn label_profile(&self) -> &str {
+        match self {
+            Language::English => "Profile:",
+            Language::German => "Profil:",
+        }
+    }
+
+    pub fn tooltip_activate_profile(&self) -> &str {
+        match self {
+            Language::English => "Activate all versions of the profile at once",
+            Language::German => "Alle Versionen des Profils auf einmal aktivieren",
+        }
+    }
+
+    pub fn tooltip_delete_profile(&self) -> &str {
+        match self {
+            Language::English => "Delete profile",
+            Language::German => "Profil löschen",
+        }
+    }
+
+    pub fn hint_profile_name(&self) -> &str {
+        match self {
+            Language::English => "New profile",
+            Language::German => "Neues Profil",
+        }
+    }
+
+    pub fn tooltip_save_profile(&self) -> &str {
+        match self {
+            Language::English => "Save the active version of every group as a profile",
+            Language::German => "Die aktive Version jeder Gruppe als Profil speichern",
+        }
+    }
+
+    pub fn tooltip_profile_matches(&self) -> &str {
+        match self {
+            Language::English => "All versions of this profile are active",
+            Language::German => "Alle Versionen dieses Profils sind aktiv",
+        }
+    }
+
+    pub fn status_profile_activated(&self, name: &str) -> String {
+        match self {
+            Language::English => format!("Profile '{}' activated.", name),
+            Language::German => format!("Profil '{}' aktiviert.", name),
+        }
+    }
+
+    pub fn status_profile_saved(&self, name: &str, count: usize) -> String {
+        match self {
+            Language::English => format!("Profile '{}' saved with {} versions.", name, count),
+            Language::German => format!("Profil '{}' mit {} Versionen gespeichert.", name, count),
+        }
+    }
+
+    pub fn label_insert_position(&self) -> &str {
+        match self {
+            Language::English => "Insert:",
+            Language::German => "Einfügen:",
+        }
+    }
+
+    pub fn tooltip_insert_position(&self) -> &str {
+        match self {
+            Language::English => "Where this group's version goes in the user PATH when switching, also for profiles and projects",
+            Language::German => "Wohin die Version dieser Gruppe beim Umschalten in den Benutzer-PATH kommt, auch bei Profilen und Projekten",
+        }
+    }
+
+    pub fn position_name(&self, position: &InsertPosition) -> &str {
+        match (self, position) {
+            (Language::English, InsertPosition::Prepend) => "At the front",
+            (Language::German, InsertPosition::Prepend) => "Vorne",
+            (Language::English, InsertPosition::Append) => "At the end",
+            (Language::German, InsertPosition::Append) => "Hinten",
+            (Language::English, InsertPosition::Replace) => "In place",
+            (Language::German, InsertPosition::Replace) => "An alter Stelle",
+            (Language::English, InsertPosition::Before(_)) => "Before entry",
+            (Language::German, InsertPosition::Before(_)) => "Vor Eintrag",
+            (Language::English, InsertPosition::After(_)) => "After entry",
+            (Language::German, InsertPosition::After(_)) => "Nach Eintrag",
+        }
+    }
+
+    pub fn hint_anchor(&self) -> &str {
+        match self {
+            Language::English => "Choose entry",
+            Language::German => "Eintrag wählen",
+        }
+    }
+
+    pub fn tooltip_path_dirs(&self, separator: char) -> String {
+        match self {
+            Language::English => format!("Several folders separated by '{}' (e.g. for Python also Scripts) are activated together", separator),
+            Language::German => format!("Mehrere Ordner, getrennt durch '{}' (z.B. bei Python auch Scripts), werden gemeinsam aktiviert", separator),
+        }
+    }
+
+    pub fn tooltip_partly_active(&self, missing: &str) -> String {
+        match self {
+            Language::English => format!("Only partly in PATH, missing:\n{}\n\nActivate again or use the cleaner to add them.", missing),
+            Language::German => format!("Nur teilweise im PATH, es fehlt:\n{}\n\nErneut aktivieren oder mit dem Cleaner ergänzen.", missing),
+        }
+    }
+
+    pub fn label_target_var(&self) -> &str {
+        match self {
+            Language::English => "Variable:",
+            Language::German => "Variable:",
+        }
+    }
+
+    pub fn tooltip_target_var(&self) -> &str {
+        match self {
+            Language::English => "The list variable this group's versions go into (PATH, PYTHONPATH, CLASSPATH, ...). Shims only work for PATH.",
+            Language::German => "Die Listen-Variable, in die die Versionen dieser Gruppe kommen (PATH, PYTHONPATH, CLASSPATH, ...). Shims gibt es nur für PATH.",
+        }
+    }
+
+    pub fn hint_target_var(&self) -> &str {
+        match self {
+            Language::English => "Other variable",
+            Language::German => "Andere Variable",
+        }
+    }
+
+    pub fn label_lists(&self) -> &str {
+        match self {
+            Language::English => "Further lists:",
+            Language::German => "Weitere Listen:",
+        }
+    }
+
+    pub fn tooltip_lists(&self, separator: char) -> String {
+        match self {
+            Language::English => format!("One variable per line, e.g. PYTHONPATH=dir1{}dir2. These folders are set together with the version.", separator),
+            Language::German => format!("Eine Variable pro Zeile, z.B. PYTHONPATH=ordner1{}ordner2. Diese Ordner werden zusammen mit der Version gesetzt.", separator),
+        }
+    }
+
+    pub fn status_var_set(&self, name: &str) -> String {
+        match self {
+            Language::English => format!("Variable '{}' saved.", name),
+            Language::German => format!("Variable '{}' gespeichert.", name),
+        }
+    }
+
+    pub fn status_var_removed(&self, name: &str) -> String {
+        match self {
+            Language::English => format!("Variable '{}' deleted.", name),
+            Language::German => format!("Variable '{}' gelöscht.", name),
+        }
+    }
+
+    pub fn tooltip_env_editor(&self) -> &str {
+        match self {
+            Language::English => "Edit environment variables",
+            Language::German => "Umgebungsvariablen bearbeiten",
+        }
+    }
+
+    pub fn window_env_title(&self) -> &str {
+        match self {
+            Language::English => "Environment Variables",
+            Language::German => "Umgebungsvariablen",
+        }
+    }
+
+    pub fn label_var_name(&self) -> &str {
+        match self {
+            Language::English => "Name:",
+            Language::German => "Name:",
+        }
+    }
+
+    pub fn label_var_value(&self) -> &str {
+        match self {
+            Language::English => "Value:",
+            Language::German => "Wert:",
+        }
+    }
+
+    pub fn label_expandable(&self) -> &str {
+        match self {
+            Language::English => "Expandable",
+            Language::German => "Erweiterbar",
+        }
+    }
+
+    pub fn tooltip_expandable(&self) -> &str {
+        match self {
+            Language::English => "References like %USERPROFILE% or $HOME are resolved. Otherwise the value is used literally.",
+            Language::German => "Verweise wie %USERPROFILE% oder $HOME werden aufgelöst. Sonst gilt der Wert wörtlich.",
+        }
+    }
+
+    pub fn btn_new_var(&self) -> &str {
+        match self {
+            Language::English => "New variable",
+            Language::German => "Neue Variable",
+        }
+    }
+
+    pub fn label_no_vars(&self) -> &str {
+        match self {
+            Language::English => "No user variables set.",
+            Language::German => "Keine Benutzervariablen gesetzt.",
+        }
+    }
+
+    pub fn status_hooks_failed(&self, count: usize) -> String {
+        match self {
+            Language::English => format!("{} hook(s) failed, see history.", count),
+            Language::German => format!("{} Hook(s) fehlgeschlagen, siehe Verlauf.", count),
+        }
+    }
+
+    pub fn tooltip_hooks(&self) -> &str {
+        match self {
+            Language::English => "Commands that run before/after activating a version of this group",
+            Language::German => "Befehle, die vor/nach dem Aktivieren einer Version dieser Gruppe laufen",
+        }
+    }
+
+    pub fn window_hooks_title(&self) -> &str {
+        match self {
+            Language::English => "Activation Hooks",
+            Language::German => "Aktivierungs-Hooks",
+        }
+    }
+
+    pub fn label_hooks(&self) -> &str {
+        match self {
+            Language::English => "Hooks:",
+            Language::German => "Hooks:",
+        }
+    }
+
+    pub fn tooltip_hook_lines(&self) -> &str {
+        match self {
+            Language::English => "One command per line: \"pre: ...\" runs before switching (a failure cancels the switch), \"post: ...\" runs afterwards with the new environment. ${path} is the version folder.",
+            Language::German => "Ein Befehl pro Zeile: \"pre: ...\" läuft vor dem Umschalten (ein Fehler bricht es ab), \"post: ...\" danach mit der neuen Umgebung. ${path} ist der Ordner der Version.",
+        }
+    }
+
+    pub fn status_switching(&self) -> &str {
+        match self {
+            Language::English => "Switching...",
+            Language::German => "Wird umgeschaltet...",
+        }
+    }
+}
@@ -0,0 +1,218 @@
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+use crate::backend::EnvBackend;
+use crate::logic::{self, ListValues};
+use crate::path_list::{self, PathList};
+use crate::shims;
+use crate::types::{GroupTarget, SwitchMode, VersionEntry};
+
+/// Woher ein Eintrag im wirksamen PATH stammt.
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum PathLayer {
+    /// System-PATH für alle Benutzer (nur lesbar).
+    Machine,
+    /// Benutzer-PATH, den dieses Tool verwaltet.
+    User,
+    /// Einträge, die nur im laufenden Prozess stehen (z.B. vom Terminal gesetzt).
+    Session,
+}
+
+/// Ein Eintrag des wirksamen PATH mit seiner Herkunft.
+#[derive(Clone, Debug)]
+pub struct LayeredEntry {
+    pub layer: PathLayer,
+    pub entry: String,
+}
+
+/// Ein Programm der aktiven Version, das von einem früheren Eintrag verdeckt wird.
+#[derive(Clone, Debug)]
+pub struct Shadow {
+    pub name: String,
+    pub by: LayeredEntry,
+}
+
+/// Liest den System-PATH. Unter Windows aus der Registry, sonst aus `/etc/environment`
+/// (oder die üblichen Standardordner, wenn dort nichts steht).
+pub fn machine_path() -> String {
+    #[cfg(windows)]
+    {
+        use winreg::enums::*;
+        use winreg::RegKey;
+
+        RegKey::predef(HKEY_LOCAL_MACHINE)
+            .open_subkey(r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment")
+            .and_then(|key| key.get_value::<String, _>("Path"))
+            .unwrap_or_default()
+    }
+    #[cfg(not(windows))]
+    {
+        std::fs::read_to_string("/etc/environment").ok()
+            .and_then(|content| {
+                content.lines()
+                    .filter_map(|l| l.trim().strip_prefix("PATH="))
+                    .map(|v| v.trim_matches('"').to_string())
+                    .next_back()
+            })
+            .unwrap_or_else(|| "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin".to_string())
+    }
+}
+
+/// PATH des laufenden Prozesses.
+pub fn session_path() -> String {
+    std::env::var("PATH").unwrap_or_default()
+}
+
+/// Setzt die Ebenen in der Reihenfolge zusammen, in der das System sucht.
+///
+/// Windows hängt den Benutzer-PATH hinter den System-PATH, die Shell-Startdateien
+/// setzen ihn dagegen nach vorne. Einträge, die nur im Prozess stehen, kommen ans Ende.
+pub fn effective_path(machine: &str, user: &str, session: &str) -> Vec<LayeredEntry> {
+    let layer = |layer: PathLayer, value: &str| {
+        PathList::parse(value).iter().map(|entry| LayeredEntry { layer, entry: entry.clone() }).collect::<Vec<_>>()
+    };
+
+    let mut result = if cfg!(windows) {
+        [layer(PathLayer::Machine, machine), layer(PathLayer::User, user)].concat()
+    } else {
+        [layer(PathLayer::User, user), layer(PathLayer::Machine, machine)].concat()
+    };
+
+    let known: HashSet<String> = result.iter().map(|e| path_list::normalize(&e.entry)).collect();
+    let mut extra = layer(PathLayer::Session, session);
+    extra.retain(|e| !known.contains(&path_list::normalize(&e.entry)));
+    result.extend(extra);
+    result
+}
+
+/// Der wirksame PATH für neue Programme, mit dem Benutzer-PATH aus dem Backend.
+pub fn current_effective(backend: &dyn EnvBackend) -> Vec<LayeredEntry> {
+    effective_path(&machine_path(), &logic::get_current_path_var(backend), &session_path())
+}
+
+/// Programme aus `names`, die ein Eintrag aus einer anderen, früheren Ebene vor `dir` anbietet.
+pub fn find_shadows(effective: &[LayeredEntry], dir: &str, names: &HashSet<String>) -> Vec<Shadow> {
+    let Some(pos) = effective.iter().position(|e| path_list::same_entry(&e.entry, dir)) else {
+        return Vec::new();
+    };
+    let own_layer = effective[pos].layer;
+
+    let mut shadows = Vec::new();
+    let mut remaining = names.clone();
+    for earlier in effective[..pos].iter().filter(|e| e.layer != own_layer) {
+        let found = logic::executable_names(Path::new(&path_list::expand(&earlier.entry)));
+        let mut hit: Vec<&String> = remaining.intersection(&found).collect();
+        hit.sort();
+        for name in hit {
+            shadows.push(Shadow { name: name.clone(), by: earlier.clone() });
+        }
+        remaining.retain(|n| !found.contains(n));
+    }
+    shadows
+}
+
+/// Verdeckte Programme der aktiven Version jeder Gruppe (Schlüssel: Ordner der Version).
+/// Nur Gruppen, die den PATH verwalten, bieten Programme an.
+pub fn shadowed_versions(
+    effective: &[LayeredEntry],
+    mode: SwitchMode,
+    values: &ListValues,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+) -> HashMap<String, Vec<Shadow>> {
+    let shim_dir = shims::default_dir().map(|d| d.display().to_string());
+    let mut result = HashMap::new();
+
+    for (group, versions) in languages {
+        let target = logic::group_target(targets, group);
+        if !target.is_path() {
+            continue;
+        }
+        let Some(active) = logic::current_entry(mode, values, group, versions, &target) else { continue };
+        let names_in = |dir: &str| logic::executable_names(Path::new(&path_list::expand(dir)));
+        // Im Shim-Modus steht der Shim-Ordner im PATH, die Programme kommen aber aus der Version
+        let shadows: Vec<Shadow> = match (mode, &shim_dir) {
+            (SwitchMode::Shims, Some(d)) => find_shadows(effective, d, &active.dirs().flat_map(names_in).collect()),
+            _ => active.dirs().flat_map(|dir| find_shadows(effective, dir, &names_in(dir))).collect(),
+        };
+        if !shadows.is_empty() {
+            result.insert(active.path.clone(), shadows);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::PathBuf;
+
+    fn entry(layer: PathLayer, dir: &Path) -> LayeredEntry {
+        LayeredEntry { layer, entry: dir.display().to_string() }
+    }
+
+    /// Ordner mit einer ausführbaren Datei `tool`.
+    fn tool_dir(parent: &Path, name: &str) -> PathBuf {
+        let dir = parent.join(name);
+        std::fs::create_dir(&dir).unwrap();
+        #[cfg(windows)]
+        std::fs::write(dir.join("tool.exe"), "").unwrap();
+        #[cfg(not(windows))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::write(dir.join("tool"), "#!/bin/sh\n").unwrap();
+            std::fs::set_permissions(dir.join("tool"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        dir
+    }
+
+    fn names() -> HashSet<String> {
+        HashSet::from(["tool".to_string()])
+    }
+
+    #[test]
+    fn earlier_machine_entry_shadows_the_user_version() {
+        let tmp = tempfile::tempdir().unwrap();
+        let system = tool_dir(tmp.path(), "system");
+        let version = tool_dir(tmp.path(), "version");
+        let effective = [entry(PathLayer::Machine, &system), entry(PathLayer::User, &version)];
+
+        let shadows = find_shadows(&effective, &version.display().to_string(), &names());
+
+        assert_eq!(shadows.len(), 1);
+        assert_eq!(shadows[0].name, "tool");
+        assert_eq!((shadows[0].by.layer, shadows[0].by.entry.as_str()), (PathLayer::Machine, effective[0].entry.as_str()));
+    }
+
+    #[test]
+    fn entries_of_the_same_layer_and_the_version_itself_do_not_shadow() {
+        let tmp = tempfile::tempdir().unwrap();
+        let other = tool_dir(tmp.path(), "other");
+        let version = tool_dir(tmp.path(), "version");
+        let dir = version.display().to_string();
+
+        let same_layer = [entry(PathLayer::User, &other), entry(PathLayer::User, &version)];
+        assert!(find_shadows(&same_layer, &dir, &names()).is_empty());
+
+        // Steht die Version auch im System-PATH, verdeckt sie sich nicht selbst
+        let twice = [entry(PathLayer::Machine, &version), entry(PathLayer::User, &version)];
+        assert!(find_shadows(&twice, &dir, &names()).is_empty());
+    }
+
+    #[test]
+    fn session_entries_come_last_and_only_once() {
+        let session: PathList = ["/opt/node20", "/usr/bin", "/tmp/bin"].iter().map(|p| p.to_string()).collect();
+
+        let effective = effective_path("/usr/bin", "/opt/node20", &session.to_string());
+
+        let layers: Vec<(PathLayer, &str)> = effective.iter().map(|e| (e.layer, e.entry.as_str())).collect();
+        let expected = if cfg!(windows) {
+            [(PathLayer::Machine, "/usr/bin"), (PathLayer::User, "/opt/node20"), (PathLayer::Session, "/tmp/bin")]
+        } else {
+            [(PathLayer::User, "/opt/node20"), (PathLayer::Machine, "/usr/bin"), (PathLayer::Session, "/tmp/bin")]
+        };
+        assert_eq!(layers, expected);
+    }
+}
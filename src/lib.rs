@@ -0,0 +1,46 @@
+//! Version Switcher: Versionen von Programmiersprachen und Tools über den PATH umschalten.
+//!
+//! Die Bibliothek enthält alles, was GUI und CLI gemeinsam nutzen:
+//!
+//! * [`types`]: Datentypen wie [`types::VersionEntry`] (ein Ordner mit Alias).
+//! * [`config`]: Gruppen, Verlauf und Einstellungen ([`config::Config`]), gespeichert wie von der GUI.
+//! * [`backend`]: Wo Umgebungsvariablen gelesen und geschrieben werden ([`backend::EnvBackend`]).
+//! * [`logic`]: Umschalten, Bereinigen sowie Import und Export.
+//!
+//! ```no_run
+//! use version_switcher::{backend, config::Config, logic};
+//!
+//! let config = Config::load().unwrap();
+//! let backend = backend::default_backend();
+//! let versions = &config.languages["Python"];
+//! let entry = config.find_entry("Python", "3.11").unwrap();
+//! logic::switch_version(backend.as_ref(), versions, entry, &config.group_target("Python")).unwrap();
+//! ```
+//!
+//! Die grafische Oberfläche ([`app`]) ist nur mit dem Feature `gui` (Standard) enthalten.
+
+pub mod types;
+pub mod backend;
+pub mod shell_profile;
+pub mod config;
+pub mod logic;
+pub mod path_list;
+pub mod layers;
+pub mod which;
+pub mod audit;
+pub mod pins;
+pub mod profiles;
+pub mod shims;
+pub mod env_vars;
+pub mod hooks;
+pub mod user_env;
+pub mod discovery;
+pub mod probe;
+pub mod snapshots;
+pub mod language;
+pub mod cli;
+
+#[cfg(feature = "gui")]
+pub mod style;
+#[cfg(feature = "gui")]
+pub mod app;
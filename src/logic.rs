@@ -1,51 +1,520 @@
-use std::collections::{HashMap, HashSet};
-use std::path::Path;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::path::{Path, PathBuf};
 use std::fs::File;
 use std::io::{BufReader, BufWriter};
-use winreg::enums::*;
-use winreg::RegKey;
-use notify_rust::Notification;
 
-use crate::types::{CleanerEntry, IssueType, VersionEntry};
+use crate::backend::EnvBackend;
+use crate::env_vars::EnvPlan;
+use crate::path_list::{self, PathList};
+use crate::shims;
+use crate::snapshots;
+use crate::types::{CleanerEntry, CleanerFix, GroupTarget, InsertPosition, IssueType, PathChange, PathWarning, SwitchMode, VersionEntry};
 
-const ENV_KEY: &str = "Environment";
+/// Name der PATH-Variable auf dieser Plattform.
+#[cfg(windows)]
+pub const PATH_VAR: &str = "Path";
+#[cfg(not(windows))]
+pub const PATH_VAR: &str = "PATH";
 
-// --- REGISTRY FUNKTIONEN ---
+/// Bekannte Listen-Variablen, die eine Gruppe statt PATH verwalten kann (Vorschläge in der GUI).
+pub const LIST_VARS: [&str; 6] = [PATH_VAR, "PYTHONPATH", "CLASSPATH", "LD_LIBRARY_PATH", "PKG_CONFIG_PATH", "MANPATH"];
 
-pub fn get_current_path_var() -> String {
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let env = hkcu.open_subkey(ENV_KEY).unwrap_or_else(|_| hkcu.create_subkey(ENV_KEY).unwrap().0);
-    env.get_value("Path").unwrap_or_default()
+/// Ab dieser Länge wird standardmäßig gewarnt (einstellbar über `Config::path_length_warning`).
+/// Unter Windows kürzen ältere Programme (z.B. `setx`) längere Werte, anderswo gibt es kein praktisches Limit.
+#[cfg(windows)]
+pub const DEFAULT_PATH_LENGTH_WARNING: usize = 2047;
+#[cfg(not(windows))]
+pub const DEFAULT_PATH_LENGTH_WARNING: usize = 32767;
+
+/// Längere Werte werden nicht geschrieben. Windows erlaubt höchstens 32767 Zeichen pro Variable,
+/// Linux höchstens 128 KiB pro Eintrag der Umgebung.
+#[cfg(windows)]
+pub const PATH_HARD_LIMIT: usize = 32767;
+#[cfg(not(windows))]
+pub const PATH_HARD_LIMIT: usize = 131072;
+
+// --- UMGEBUNGS FUNKTIONEN ---
+
+/// Werte von Listen-Variablen wie PATH oder `PYTHONPATH` (Name -> Wert).
+pub type ListValues = BTreeMap<String, String>;
+
+/// Ob zwei Variablennamen dieselbe Variable meinen (unter Windows ohne Groß-/Kleinschreibung).
+pub fn same_var(a: &str, b: &str) -> bool {
+    if cfg!(windows) { a.eq_ignore_ascii_case(b) } else { a == b }
 }
 
-pub fn set_path_var(new_path: String) -> Result<(), String> {
-    let hkcu = RegKey::predef(HKEY_CURRENT_USER);
-    let env = match hkcu.open_subkey_with_flags(ENV_KEY, KEY_WRITE) {
-        Ok(key) => key,
-        Err(e) => return Err(format!("Registry Error: {}", e)),
-    };
+/// Wert von `var` aus `values` (leer, wenn er fehlt).
+pub fn list_value<'a>(values: &'a ListValues, var: &str) -> &'a str {
+    values.iter().find(|(name, _)| same_var(name, var)).map(|(_, v)| v.as_str()).unwrap_or_default()
+}
+
+/// Liest den Benutzer-PATH aus dem Backend (leer, wenn nicht gesetzt).
+pub fn get_current_path_var(backend: &dyn EnvBackend) -> String {
+    get_list_var(backend, PATH_VAR)
+}
+
+/// Schreibt den Benutzer-PATH und benachrichtigt das System.
+/// Der alte Wert wird vorher als Snapshot gesichert (`reason` beschreibt die Änderung).
+pub fn set_path_var(backend: &dyn EnvBackend, new_path: String, reason: &str) -> Result<(), String> {
+    set_list_var(backend, PATH_VAR, new_path, reason)
+}
+
+/// Liest eine Listen-Variable des Benutzers (leer, wenn nicht gesetzt).
+pub fn get_list_var(backend: &dyn EnvBackend, var: &str) -> String {
+    backend.read_var(var).unwrap_or_default()
+}
+
+/// Schreibt eine Listen-Variable wie `set_path_var`. Ein leerer Wert löscht die Variable.
+pub fn set_list_var(backend: &dyn EnvBackend, var: &str, new_value: String, reason: &str) -> Result<(), String> {
+    write_lists(backend, &ListValues::from([(var.to_string(), new_value)]), reason)
+}
+
+/// Schreibvorgang für einen Listenwert: leer löscht die Variable (außer PATH).
+fn list_write(var: &str, value: &str) -> Option<String> {
+    if value.is_empty() && !same_var(var, PATH_VAR) { None } else { Some(value.to_string()) }
+}
+
+/// Schreibt `writes` (`None` löscht) ohne Snapshot und Benachrichtigung. Schlägt einer fehl,
+/// bekommen die schon geschriebenen Variablen ihren alten Wert zurück. Liefert die alten
+/// Werte der geänderten Variablen, um sie später zurücksetzen zu können.
+fn store_all(backend: &dyn EnvBackend, writes: &[(String, Option<String>)]) -> Result<Vec<(String, Option<String>)>, String> {
+    let mut undo = Vec::new();
+    for (name, value) in writes {
+        let old = backend.read_var(name);
+        if old == value.as_deref().map(|v| backend.stored_value(name, v)) {
+            continue;
+        }
+        let result = match value {
+            Some(value) => backend.write_var(name, value),
+            None => backend.remove_var(name),
+        };
+        if let Err(e) = result {
+            restore_all(backend, &undo);
+            return Err(e);
+        }
+        undo.push((name.clone(), old));
+    }
+    Ok(undo)
+}
+
+/// Setzt die Variablen aus `undo` (von `store_all`) auf ihre alten Werte zurück.
+fn restore_all(backend: &dyn EnvBackend, undo: &[(String, Option<String>)]) {
+    for (name, old) in undo.iter().rev() {
+        let _ = match old {
+            Some(value) => backend.write_var(name, value),
+            None => backend.remove_var(name),
+        };
+    }
+}
+
+fn check_length(var: &str, value: &str) -> Result<(), String> {
+    let length = path_length(value);
+    if length > PATH_HARD_LIMIT {
+        return Err(format!("{} would be {} characters long, the limit is {}. Nothing was written.", var, length, PATH_HARD_LIMIT));
+    }
+    Ok(())
+}
+
+/// Liest die Werte von `vars`.
+pub fn read_lists(backend: &dyn EnvBackend, vars: &[String]) -> ListValues {
+    vars.iter().map(|var| (var.clone(), get_list_var(backend, var))).collect()
+}
+
+/// Schreibt alle geänderten Werte aus `planned`, PATH zuerst, und benachrichtigt das System einmal.
+/// Alles oder nichts, siehe `write_activation`.
+pub fn write_lists(backend: &dyn EnvBackend, planned: &ListValues, reason: &str) -> Result<(), String> {
+    write_activation(backend, planned, &EnvPlan::default(), &[], reason)
+}
+
+/// Länge eines Werts so, wie das System sie zählt (unter Windows in UTF-16-Zeichen).
+pub fn path_length(value: &str) -> usize {
+    if cfg!(windows) { value.encode_utf16().count() } else { value.len() }
+}
+
+/// Warnung, wenn `value` länger als `warn_length` oder das feste Limit ist.
+pub fn length_warning(value: &str, warn_length: usize) -> Option<PathWarning> {
+    let length = path_length(value);
+    if length > PATH_HARD_LIMIT {
+        Some(PathWarning::ExceedsLimit { length, limit: PATH_HARD_LIMIT })
+    } else if length > warn_length {
+        Some(PathWarning::TooLong { length, limit: warn_length })
+    } else {
+        None
+    }
+}
 
-    match env.set_value("Path", &new_path) {
-        Ok(_) => {
-            // Windows benachrichtigen (Broadcast)
-            use std::ptr;
-            #[cfg(windows)]
-            unsafe {
-                use winapi::um::winuser::{SendMessageTimeoutA, HWND_BROADCAST, WM_SETTINGCHANGE, SMTO_ABORTIFHUNG};
-                let lp_param = std::ffi::CString::new("Environment").unwrap();
-                SendMessageTimeoutA(
-                    HWND_BROADCAST, WM_SETTINGCHANGE, 0, lp_param.as_ptr() as isize,
-                    SMTO_ABORTIFHUNG, 5000, ptr::null_mut(),
-                );
+/// Vergleicht zwei PATH-Werte Eintrag für Eintrag (in der Reihenfolge von `new`).
+pub fn diff_paths(old: &str, new: &str) -> Vec<PathChange> {
+    let a = PathList::parse(old);
+    let b = PathList::parse(new);
+    let a_norm: Vec<String> = a.iter().map(|p| path_list::normalize(p)).collect();
+    let b_norm: Vec<String> = b.iter().map(|p| path_list::normalize(p)).collect();
+    let (a, b) = (a.entries(), b.entries());
+
+    // Längste gemeinsame Teilfolge, von hinten aufgebaut
+    let mut lcs = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for i in (0..a.len()).rev() {
+        for j in (0..b.len()).rev() {
+            lcs[i][j] = if a_norm[i] == b_norm[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < a.len() && j < b.len() {
+        if a_norm[i] == b_norm[j] {
+            changes.push(PathChange::Kept(b[j].clone()));
+            i += 1;
+            j += 1;
+        } else if lcs[i + 1][j] > lcs[i][j + 1] {
+            changes.push(PathChange::Removed(a[i].clone()));
+            i += 1;
+        } else {
+            changes.push(PathChange::Added(b[j].clone()));
+            j += 1;
+        }
+    }
+    changes.extend(a[i..].iter().map(|p| PathChange::Removed(p.clone())));
+    changes.extend(b[j..].iter().map(|p| PathChange::Added(p.clone())));
+
+    // Entfernt und woanders wieder hinzugefügt heißt verschoben
+    let removed: Vec<String> = changes.iter()
+        .filter_map(|c| match c { PathChange::Removed(p) => Some(path_list::normalize(p)), _ => None })
+        .collect();
+    let added: Vec<String> = changes.iter()
+        .filter_map(|c| match c { PathChange::Added(p) => Some(path_list::normalize(p)), _ => None })
+        .collect();
+    changes.retain(|c| !matches!(c, PathChange::Removed(p) if added.contains(&path_list::normalize(p))));
+    for c in &mut changes {
+        if let PathChange::Added(p) = c
+            && removed.contains(&path_list::normalize(p))
+        {
+            *c = PathChange::Moved(std::mem::take(p));
+        }
+    }
+    changes
+}
+
+/// Ein geplanter Wert von `var` (meist PATH) mit Vergleich zum aktuellen Wert und Warnungen.
+#[derive(Clone, Debug)]
+pub struct PathPreview {
+    pub var: String,
+    pub current: String,
+    pub planned: String,
+    pub changes: Vec<PathChange>,
+    pub warnings: Vec<PathWarning>,
+}
+
+impl PathPreview {
+    pub fn is_unchanged(&self) -> bool {
+        self.current == self.planned
+    }
+}
+
+/// Berechnet die Vorschau für einen geplanten PATH, ohne etwas zu schreiben.
+/// Ab `warn_length` Zeichen gibt es eine Warnung zur Länge.
+pub fn preview_path(current: &str, planned: &str, warn_length: usize) -> PathPreview {
+    preview_list(PATH_VAR, current, planned, warn_length)
+}
+
+/// Vorschau für alle Variablen aus `planned`: PATH immer, die anderen nur, wenn sie sich ändern.
+pub fn preview_lists(values: &ListValues, planned: &ListValues, warn_length: usize) -> Vec<PathPreview> {
+    let mut previews = vec![preview_path(list_value(values, PATH_VAR), list_value(planned, PATH_VAR), warn_length)];
+    for (var, value) in planned.iter().filter(|(var, _)| !same_var(var, PATH_VAR)) {
+        let current = list_value(values, var);
+        if current != value {
+            previews.push(preview_list(var, current, value, warn_length));
+        }
+    }
+    previews
+}
+
+/// Vorschau für eine Listen-Variable. Verdeckte Programme werden nur im PATH gesucht.
+pub fn preview_list(var: &str, current: &str, planned: &str, warn_length: usize) -> PathPreview {
+    let changes = diff_paths(current, planned);
+    if !same_var(var, PATH_VAR) {
+        // Die einstellbare Warnung gilt nur für den PATH, das feste Limit für alle
+        let warnings = length_warning(planned, PATH_HARD_LIMIT).into_iter().collect();
+        return PathPreview { var: var.to_string(), current: current.to_string(), planned: planned.to_string(), changes, warnings };
+    }
+    let mut warnings: Vec<PathWarning> = length_warning(planned, warn_length).into_iter().collect();
+
+    // Nur neue oder verschobene Ordner prüfen, der Rest war vorher schon so
+    let parts = PathList::parse(planned);
+    let parts = parts.entries();
+    let mut names_cache: HashMap<usize, HashSet<String>> = HashMap::new();
+    for (idx, dir) in parts.iter().enumerate() {
+        let touched = changes.iter().any(|c| matches!(c, PathChange::Added(p) | PathChange::Moved(p) if p == dir));
+        if !touched {
+            continue;
+        }
+        for name in executable_names(Path::new(&path_list::expand(dir))) {
+            let by = (0..idx).find(|earlier| {
+                names_cache.entry(*earlier)
+                    .or_insert_with(|| executable_names(Path::new(&path_list::expand(&parts[*earlier]))))
+                    .contains(&name)
+            });
+            if let Some(by) = by {
+                warnings.push(PathWarning::Shadowed { name, dir: dir.clone(), by: parts[by].clone() });
             }
-            Ok(())
-        },
-        Err(e) => Err(format!("Write Error: {}", e)),
+        }
     }
+
+    PathPreview { var: var.to_string(), current: current.to_string(), planned: planned.to_string(), changes, warnings }
 }
 
+/// Namen der Programme in einem Ordner, so wie man sie aufruft (`python`, nicht `python.exe`).
+pub fn executable_names(dir: &Path) -> HashSet<String> {
+    list_executables(dir).iter()
+        .filter_map(|p| {
+            if cfg!(windows) {
+                p.file_stem().map(|s| s.to_string_lossy().to_lowercase())
+            } else {
+                p.file_name().map(|s| s.to_string_lossy().to_string())
+            }
+        })
+        .collect()
+}
+
+// --- SWITCH FUNKTIONEN ---
+
+/// Entfernt alle Ordner der Gruppe für `var` aus `current` und setzt die Ordner von `entry`
+/// an die Stelle, die die Position der Gruppe vorgibt.
+pub fn build_switched_list(
+    current: &str,
+    group_versions: &[VersionEntry],
+    entry: &VersionEntry,
+    var: &str,
+    target: &GroupTarget,
+) -> String {
+    let mut parts = PathList::parse(current);
+    let previous = remove_dirs(&mut parts, group_versions.iter().flat_map(|v| v.list_dirs(var, &target.var)));
+    let index = insertion_index(&parts, &target.position, previous);
+    for (offset, dir) in entry.list_dirs(var, &target.var).into_iter().enumerate() {
+        parts.insert(index + offset, dir);
+    }
+    parts.to_string()
+}
+
+/// Entfernt `dirs` und liefert die Stelle, an der der erste davon stand.
+pub fn remove_dirs<'a>(parts: &mut PathList, dirs: impl IntoIterator<Item = &'a str>) -> Option<usize> {
+    let dirs: Vec<&str> = dirs.into_iter().collect();
+    // Davor stehen nur fremde Einträge, die Stelle bleibt beim Entfernen also gültig
+    let previous = parts.iter().position(|p| dirs.iter().any(|d| path_list::same_entry(p, d)));
+    for dir in dirs {
+        parts.remove_all(dir);
+    }
+    previous
+}
+
+/// Index, an dem ein neuer Eintrag nach `position` eingefügt wird. `previous` ist die Stelle
+/// der bisherigen Version. Fehlt sie oder der Anker, kommt der Eintrag nach vorne.
+pub fn insertion_index(parts: &PathList, position: &InsertPosition, previous: Option<usize>) -> usize {
+    match position {
+        InsertPosition::Prepend => 0,
+        InsertPosition::Append => parts.len(),
+        InsertPosition::Replace => previous.unwrap_or(0),
+        InsertPosition::Before(anchor) => parts.position(anchor).unwrap_or(0),
+        InsertPosition::After(anchor) => parts.position(anchor).map(|i| i + 1).unwrap_or(0),
+    }
+}
+
+/// Liefert den Eintrag der Gruppe, der in `current` am weitesten vorne steht (also gewinnt).
+pub fn active_entry<'a>(current: &str, group_versions: &'a [VersionEntry]) -> Option<&'a VersionEntry> {
+    PathList::parse(current).iter()
+        .find_map(|p| group_versions.iter().find(|v| v.has_dir(p)))
+}
+
+/// Alle Variablen, die die Gruppe verwaltet: ihre eigene und die aus `lists` der Versionen.
+pub fn group_vars(group_versions: &[VersionEntry], target: &GroupTarget) -> Vec<String> {
+    let mut vars = vec![target.var.clone()];
+    for var in group_versions.iter().flat_map(|v| v.lists.keys()) {
+        if !vars.iter().any(|v| same_var(v, var)) {
+            vars.push(var.clone());
+        }
+    }
+    vars
+}
+
+/// Shims gibt es nur für Gruppen, die den PATH verwalten, alle anderen schalten direkt um.
+pub fn mode_for(mode: SwitchMode, target: &GroupTarget) -> SwitchMode {
+    if target.is_path() { mode } else { SwitchMode::Path }
+}
+
+/// Aktiviert `entry` und entfernt dabei alle anderen Versionen der Gruppe aus ihren Variablen.
+pub fn switch_version(
+    backend: &dyn EnvBackend,
+    group_versions: &[VersionEntry],
+    entry: &VersionEntry,
+    target: &GroupTarget,
+) -> Result<(), String> {
+    let values = read_lists(backend, &group_vars(group_versions, target));
+    let planned = planned_lists(&values, SwitchMode::Path, group_versions, entry, target)?;
+    write_lists(backend, &planned, "Switch")
+}
+
+/// Aktiviert eine Version je nach Modus über den PATH oder über Shims
+/// und setzt die zusätzlichen Variablen der Version.
+pub fn activate(
+    backend: &dyn EnvBackend,
+    mode: SwitchMode,
+    group: &str,
+    group_versions: &[VersionEntry],
+    entry: &VersionEntry,
+    target: &GroupTarget,
+) -> Result<(), String> {
+    let values = read_lists(backend, &group_vars(group_versions, target));
+    let planned = planned_lists(&values, mode, group_versions, entry, target)?;
+    let shim_plans = if mode_for(mode, target) == SwitchMode::Shims { vec![shims::plan(group, entry)] } else { Vec::new() };
+    let mut env = EnvPlan::new(backend);
+    env.add(backend, group_versions, entry);
+    write_activation(backend, &planned, &env, &shim_plans, "Switch")
+}
+
+/// Schreibt geplante Listen, die Variablen der Versionen und die Shims in einem Zug und
+/// benachrichtigt das System einmal. `reason` landet in den Snapshots der Listen.
+///
+/// Alles oder nichts: Ist ein Wert zu lang oder lassen sich Snapshots bzw. die Sicherung der
+/// Variablen nicht speichern, wird gar nichts geschrieben. Schlägt danach ein Schreibvorgang
+/// oder das Anlegen der Shims fehl, bekommen alle Variablen ihren alten Wert zurück.
+pub fn write_activation(
+    backend: &dyn EnvBackend,
+    planned: &ListValues,
+    env: &EnvPlan,
+    shim_plans: &[shims::ShimPlan],
+    reason: &str,
+) -> Result<(), String> {
+    let mut lists: Vec<(&str, String, &str)> = planned.iter()
+        .map(|(var, value)| (var.as_str(), get_list_var(backend, var), value.as_str()))
+        .filter(|(var, old, new)| *old != backend.stored_value(var, new))
+        .collect();
+    for (var, _, value) in &lists {
+        check_length(var, value)?;
+    }
+    lists.sort_by_key(|(var, _, _)| !same_var(var, PATH_VAR));
+    let shim_dir = if shim_plans.is_empty() { None } else { Some(shims::default_dir().ok_or("No directory for shims found")?) };
+
+    // Erst alles sichern, dann schreiben
+    for (var, old, _) in &lists {
+        snapshots::record(backend, var, reason, old)?;
+    }
+    env.save_backup(backend)?;
+
+    let writes: Vec<(String, Option<String>)> = lists.iter()
+        .map(|(var, _, value)| (var.to_string(), list_write(var, value)))
+        .chain(env.writes())
+        .collect();
+    let undo = match store_all(backend, &writes) {
+        Ok(undo) => undo,
+        Err(e) => {
+            let _ = env.restore_backup(backend);
+            return Err(e);
+        }
+    };
+    let shims_written = match &shim_dir {
+        Some(dir) => shims::apply(dir, shim_plans).map(|_| ()),
+        None => Ok(()),
+    };
+    if let Err(e) = shims_written {
+        restore_all(backend, &undo);
+        let _ = env.restore_backup(backend);
+        return Err(e);
+    }
+
+    if !undo.is_empty() {
+        backend.broadcast_change();
+    }
+    Ok(())
+}
+
+/// Die Werte aus `values` nach dem Aktivieren von `entry`. `values` muss die Variablen
+/// der Gruppe enthalten (siehe `group_vars`), fehlende gelten als leer.
+pub fn planned_lists(
+    values: &ListValues,
+    mode: SwitchMode,
+    group_versions: &[VersionEntry],
+    entry: &VersionEntry,
+    target: &GroupTarget,
+) -> Result<ListValues, String> {
+    let mut planned = values.clone();
+    for var in group_vars(group_versions, target) {
+        let current = list_value(values, &var);
+        let value = if mode_for(mode, target) == SwitchMode::Shims && same_var(&var, PATH_VAR) {
+            let dir = shims::default_dir().ok_or("No directory for shims found")?;
+            shims::planned_path(current, &dir, group_versions, &target.position)
+        } else {
+            build_switched_list(current, group_versions, entry, &var, target)
+        };
+        let key = planned.keys().find(|k| same_var(k, &var)).cloned().unwrap_or(var);
+        planned.insert(key, value);
+    }
+    Ok(planned)
+}
+
+/// Liefert die aktive Version einer Gruppe, passend zum Modus.
+/// `values` muss die Variable der Gruppe enthalten.
+pub fn current_entry<'a>(
+    mode: SwitchMode,
+    values: &ListValues,
+    group: &str,
+    group_versions: &'a [VersionEntry],
+    target: &GroupTarget,
+) -> Option<&'a VersionEntry> {
+    match mode_for(mode, target) {
+        SwitchMode::Path => active_entry(list_value(values, &target.var), group_versions),
+        SwitchMode::Shims => {
+            let target = shims::default_dir().and_then(|dir| shims::active_target(&dir, group))?;
+            group_versions.iter().find(|v| path_list::same_entry(&v.path, &target))
+        }
+    }
+}
+
+/// Erste Versionsnummer in einem Text, z.B. `17.0.2` aus `jdk-17.0.2`.
+pub fn extract_version(text: &str) -> Option<String> {
+    let version: String = text.chars()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit() || *c == '.')
+        .collect();
+    let version = version.trim_end_matches('.');
+    if version.is_empty() { None } else { Some(version.to_string()) }
+}
+
+// --- DATEISYSTEM ---
+
+/// Prüft, ob eine Datei ausführbar ist (Windows: Endung, Unix: Ausführungsrecht).
+pub fn is_executable(path: &Path) -> bool {
+    if !path.is_file() {
+        return false;
+    }
+    #[cfg(windows)]
+    {
+        path.extension()
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .is_some_and(|e| matches!(e.as_str(), "exe" | "bat" | "cmd" | "com"))
+    }
+    #[cfg(not(windows))]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        path.metadata().is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+    }
+}
+
+/// Alle ausführbaren Dateien direkt in `dir`, sortiert.
+pub fn list_executables(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| is_executable(p)).collect())
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+#[cfg(feature = "gui")]
 pub fn send_notification(title: &str, body: &str) {
-    Notification::new()
+    notify_rust::Notification::new()
         .summary(title)
         .body(body)
         .appname("Version Switcher")
@@ -55,88 +524,686 @@ pub fn send_notification(title: &str, body: &str) {
 
 // --- IMPORT / EXPORT FUNKTIONEN ---
 
+/// Fragt per Dialog nach einer Datei und exportiert die Gruppen dorthin.
+#[cfg(feature = "gui")]
 pub fn export_to_file(data: &HashMap<String, Vec<VersionEntry>>) -> Result<(), String> {
     if let Some(path) = rfd::FileDialog::new().set_file_name("version_switcher_config.json").save_file() {
-        let file = File::create(path).map_err(|e| e.to_string())?;
-        let writer = BufWriter::new(file);
-        serde_json::to_writer_pretty(writer, data).map_err(|e| e.to_string())?;
-        return Ok(());
+        return export_to_path(data, &path);
     }
     Err("Cancelled".to_string())
 }
 
+/// Fragt per Dialog nach einer Datei und importiert die Gruppen daraus.
+#[cfg(feature = "gui")]
 pub fn import_from_file() -> Result<HashMap<String, Vec<VersionEntry>>, String> {
     if let Some(path) = rfd::FileDialog::new().pick_file() {
-        let file = File::open(path).map_err(|e| e.to_string())?;
-        let reader = BufReader::new(file);
-        let data = serde_json::from_reader(reader).map_err(|e| e.to_string())?;
-        return Ok(data);
+        return import_from_path(&path);
     }
     Err("Cancelled".to_string())
 }
 
+/// Schreibt die Gruppen als JSON in `path`.
+pub fn export_to_path(data: &HashMap<String, Vec<VersionEntry>>, path: &Path) -> Result<(), String> {
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    let writer = BufWriter::new(file);
+    serde_json::to_writer_pretty(writer, data).map_err(|e| e.to_string())
+}
+
+/// Liest Gruppen aus einer JSON-Datei.
+pub fn import_from_path(path: &Path) -> Result<HashMap<String, Vec<VersionEntry>>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+    let reader = BufReader::new(file);
+    serde_json::from_reader(reader).map_err(|e| e.to_string())
+}
+
 // --- CLEANER FUNKTIONEN ---
 
-pub fn scan_for_issues(current_path: &str) -> Vec<CleanerEntry> {
-    let parts: Vec<&str> = current_path.split(';').filter(|s| !s.is_empty()).collect();
+/// Sucht problematische Einträge im PATH: doppelte, fehlende, falsch geschriebene usw.
+/// Für andere Listen-Variablen als PATH (`var`) sind auch Dateien erlaubt (z.B. `.jar` im `CLASSPATH`).
+pub fn scan_for_issues(var: &str, current_path: &str) -> Vec<CleanerEntry> {
+    let is_path = same_var(var, PATH_VAR);
     let mut entries = Vec::new();
-    let mut seen = HashSet::new();
+    let mut seen: HashMap<String, String> = HashMap::new();
 
-    for p in parts {
-        let p_string = p.to_string();
-        let mut issue = None;
-
-        if seen.contains(&p_string.to_lowercase()) {
-            issue = Some(IssueType::Duplicate);
-        } else {
-            seen.insert(p_string.to_lowercase());
-            if !Path::new(p).exists() {
-                issue = Some(IssueType::Missing);
+    for p in &PathList::parse(current_path) {
+        // `%USERPROFILE%\bin`, `C:\Users\me\bin\` und `c:\users\me\bin` sind derselbe Ordner
+        let normalized = path_list::normalize(p);
+        let issue = match seen.get(&normalized) {
+            Some(first) if first == p => Some(IssueType::Duplicate),
+            Some(first) => Some(IssueType::Variant { of: first.clone() }),
+            None => {
+                seen.insert(normalized, p.clone());
+                diagnose_entry(p, is_path)
             }
-        }
+        };
 
-        if let Some(iss) = issue {
+        if let Some(issue) = issue {
             entries.push(CleanerEntry {
-                path: p_string,
-                issue: iss,
-                selected: true,
+                var: var.to_string(),
+                path: p.clone(),
+                selected: issue.selected_by_default(),
+                issue,
             });
         }
     }
     entries
 }
 
-pub fn perform_cleanup(current_path: &str, issues: &[CleanerEntry]) -> (String, usize) {
-    let to_remove_missing: HashSet<String> = issues.iter()
-        .filter(|e| e.selected && e.issue == IssueType::Missing)
-        .map(|e| e.path.to_lowercase())
+/// Prüft einen einzelnen Eintrag, ohne ihn mit den anderen zu vergleichen.
+/// Programme werden nur im PATH (`is_path`) erwartet.
+fn diagnose_entry(entry: &str, is_path: bool) -> Option<IssueType> {
+    let names = path_list::unresolved_vars(entry);
+    if !names.is_empty() {
+        return Some(IssueType::UnresolvedVar { names });
+    }
+
+    let expanded = path_list::expand(entry);
+    // `lib/*` im CLASSPATH meint alle Archive in `lib`
+    let expanded = match expanded.strip_suffix('*') {
+        Some(dir) if !is_path => dir.to_string(),
+        _ => expanded,
+    };
+    let dir = PathBuf::from(expanded);
+    if !dir.is_absolute() {
+        return Some(IssueType::Relative);
+    }
+    if !dir.exists() {
+        return Some(IssueType::Missing);
+    }
+    if !is_path && dir.is_file() {
+        return None;
+    }
+    if !dir.is_dir() {
+        // Ordner der Datei, so geschrieben wie im Eintrag (Variablen bleiben erhalten)
+        let parent = Path::new(entry.trim()).parent()
+            .map(|p| p.display().to_string())
+            .filter(|p| !p.is_empty() && Path::new(&path_list::expand(p)).is_dir());
+        return Some(IssueType::NotADirectory { parent });
+    }
+
+    // Nicht lesbare Ordner werden nicht gemeldet
+    let mut content = std::fs::read_dir(&dir).ok()?;
+    if content.next().is_none() {
+        return Some(IssueType::EmptyDir);
+    }
+    if is_path && list_executables(&dir).is_empty() {
+        return Some(IssueType::NoExecutables);
+    }
+    None
+}
+
+/// Sucht Programme, die die aktiven Versionen mehrerer Gruppen gleichzeitig anbieten.
+///
+/// Im PATH-Modus gewinnt die Gruppe, deren Ordner weiter vorne steht, im Shim-Modus die zuletzt
+/// aktivierte. Gemeldet wird die Version der verlierenden Gruppe, nicht zum Bereinigen ausgewählt.
+/// Gruppen mit einer anderen Variable als PATH bieten keine Programme an.
+pub fn scan_for_conflicts(
+    mode: SwitchMode,
+    values: &ListValues,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+) -> Vec<CleanerEntry> {
+    let parts = PathList::parse(list_value(values, PATH_VAR));
+    let shim_dir = shims::default_dir();
+
+    let mut active: Vec<(usize, &String, &VersionEntry, HashSet<String>)> = languages.iter()
+        .filter_map(|(group, versions)| {
+            let target = group_target(targets, group);
+            if !target.is_path() {
+                return None;
+            }
+            let entry = current_entry(mode, values, group, versions, &target)?;
+            let pos = entry.dirs().filter_map(|d| parts.position(d)).min().unwrap_or(usize::MAX);
+            let names = entry.dirs().flat_map(|d| executable_names(Path::new(&path_list::expand(d)))).collect();
+            Some((pos, group, entry, names))
+        })
         .collect();
+    active.sort_by(|a, b| (a.0, a.1).cmp(&(b.0, b.1)));
+
+    // (Verlierer, Gewinner) -> Programme
+    let mut conflicts: Vec<((usize, usize), Vec<String>)> = Vec::new();
+    for i in 0..active.len() {
+        for j in i + 1..active.len() {
+            let mut common: Vec<&String> = active[i].3.intersection(&active[j].3).collect();
+            common.sort();
+            for name in common {
+                let i_wins = match (mode, &shim_dir) {
+                    (SwitchMode::Shims, Some(dir)) => shims::command_owner(dir, name).as_ref() != Some(active[j].1),
+                    _ => true,
+                };
+                let key = if i_wins { (j, i) } else { (i, j) };
+                match conflicts.iter_mut().find(|(k, _)| *k == key) {
+                    Some((_, names)) => names.push(name.clone()),
+                    None => conflicts.push((key, vec![name.clone()])),
+                }
+            }
+        }
+    }
+
+    conflicts.into_iter()
+        .map(|((loser, winner), names)| CleanerEntry {
+            var: PATH_VAR.to_string(),
+            path: active[loser].2.path.clone(),
+            issue: IssueType::Shadowed { group: active[loser].1.clone(), by: active[winner].1.clone(), names },
+            selected: false,
+        })
+        .collect()
+}
+
+/// Sucht Versionen, von denen nur ein Teil der Ordner in einer ihrer Variablen steht (z.B. `python`,
+/// aber nicht `python\Scripts`). Gemeldet wird der erste vorhandene Ordner, die fehlenden kommen dahinter.
+/// Im Shim-Modus stehen die Ordner der Versionen nicht im PATH, dort gibt es nichts zu melden.
+pub fn scan_for_incomplete(
+    mode: SwitchMode,
+    values: &ListValues,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+) -> Vec<CleanerEntry> {
+    let mut groups: Vec<_> = languages.iter().collect();
+    groups.sort_by_key(|(group, _)| *group);
+
+    let mut entries = Vec::new();
+    for (group, versions) in groups {
+        let target = group_target(targets, group);
+        for var in group_vars(versions, &target) {
+            if mode_for(mode, &target) == SwitchMode::Shims && same_var(&var, PATH_VAR) {
+                continue;
+            }
+            let parts = PathList::parse(list_value(values, &var));
+            for v in versions {
+                let (present, missing): (Vec<&str>, Vec<&str>) = v.list_dirs(&var, &target.var).into_iter().partition(|d| parts.contains(d));
+                if let Some(first) = present.first().filter(|_| !missing.is_empty()) {
+                    let issue = IssueType::Incomplete {
+                        group: group.clone(),
+                        alias: v.alias.clone(),
+                        missing: missing.iter().map(|d| d.to_string()).collect(),
+                    };
+                    let selected = issue.selected_by_default();
+                    entries.push(CleanerEntry { var: var.clone(), path: first.to_string(), issue, selected });
+                }
+            }
+        }
+    }
+    entries
+}
+
+/// Alle Probleme in den Variablen aus `values`: einzelne Einträge, Konflikte und unvollständige Versionen.
+pub fn scan_all(
+    mode: SwitchMode,
+    values: &ListValues,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+) -> Vec<CleanerEntry> {
+    let mut issues: Vec<CleanerEntry> = values.iter().flat_map(|(var, value)| scan_for_issues(var, value)).collect();
+    issues.extend(scan_for_conflicts(mode, values, languages, targets));
+    issues.extend(scan_for_incomplete(mode, values, languages, targets));
+    issues
+}
+
+/// Behebt die ausgewählten Probleme in allen Variablen aus `values`.
+/// Liefert die neuen Werte und die Anzahl geänderter Einträge.
+pub fn cleanup_all(values: &ListValues, issues: &[CleanerEntry]) -> (ListValues, usize) {
+    let mut planned = ListValues::new();
+    let mut changed = 0;
+    for (var, value) in values {
+        let (new, count) = perform_cleanup(var, value, issues);
+        planned.insert(var.clone(), new);
+        changed += count;
+    }
+    (planned, changed)
+}
+
+/// Einstellungen der Gruppe aus `targets` (fehlt sie: PATH, vorne).
+pub fn group_target(targets: &HashMap<String, GroupTarget>, group: &str) -> GroupTarget {
+    targets.get(group).cloned().unwrap_or_default()
+}
+
+/// Behebt die ausgewählten Probleme von `var` in `current_path`.
+/// Liefert den neuen Wert und die Anzahl geänderter Einträge.
+pub fn perform_cleanup(var: &str, current_path: &str, issues: &[CleanerEntry]) -> (String, usize) {
+    let mut actions: HashMap<String, CleanerFix> = HashMap::new();
+    // Der erste Eintrag (evtl. mit `%VAR%`) bleibt, spätere Duplikate fliegen raus
+    let mut to_deduplicate = HashSet::new();
+    for e in issues.iter().filter(|e| e.selected && same_var(&e.var, var)) {
+        match e.issue.fix() {
+            Some(CleanerFix::KeepFirst) => { to_deduplicate.insert(path_list::normalize(&e.path)); }
+            Some(fix) => { actions.insert(path_list::normalize(&e.path), fix); }
+            None => {}
+        }
+    }
+
+    let mut parts = Vec::new();
+    let mut seen = HashSet::new();
+    let mut changed = 0;
+
+    for p in &PathList::parse(current_path) {
+        let normalized = path_list::normalize(p);
+        let mut inserted = Vec::new();
+        let new = match actions.get(&normalized) {
+            Some(CleanerFix::Remove) => None,
+            Some(CleanerFix::InsertAfter(dirs)) => {
+                inserted = dirs.clone();
+                Some(p.clone())
+            }
+            // Steht der Ersatz schon im PATH, fällt der Eintrag einfach weg
+            Some(CleanerFix::Replace(with)) => Some(with.clone()).filter(|w| !seen.contains(&path_list::normalize(w))),
+            _ if to_deduplicate.contains(&normalized) && seen.contains(&normalized) => None,
+            _ => Some(p.clone()),
+        };
+
+        if new.as_ref() != Some(p) {
+            changed += 1;
+        }
+        if let Some(new) = new {
+            seen.insert(path_list::normalize(&new));
+            parts.push(new);
+        }
+        for dir in inserted {
+            if seen.insert(path_list::normalize(&dir)) {
+                changed += 1;
+                parts.push(dir);
+            }
+        }
+    }
+
+    (parts.into_iter().collect::<PathList>().to_string(), changed)
+}
+
+/// Ergebnis von `compact_path`: ein kürzerer PATH, der auf dieselben Ordner zeigt.
+#[derive(Clone, Debug)]
+pub struct Compaction {
+    pub path: String,
+    /// Anzahl entfernter Duplikate.
+    pub duplicates: usize,
+    /// Ersetzte Präfixe als (Verweis, Ordner), z.B. (`%LOCALAPPDATA%`, `C:\Users\me\AppData\Local`).
+    pub substitutions: Vec<(String, String)>,
+}
+
+impl Compaction {
+    /// Eingesparte Zeichen gegenüber `current`.
+    pub fn saved(&self, current: &str) -> usize {
+        path_length(current).saturating_sub(path_length(&self.path))
+    }
+}
+
+/// Variablen, deren Wert als Präfix durch einen Verweis ersetzt werden darf.
+#[cfg(windows)]
+const COMPACT_VARS: [&str; 7] = ["USERPROFILE", "LOCALAPPDATA", "APPDATA", "ProgramFiles", "ProgramFiles(x86)", "ProgramData", "SystemRoot"];
+#[cfg(not(windows))]
+const COMPACT_VARS: [&str; 1] = ["HOME"];
 
-    let to_deduplicate: HashSet<String> = issues.iter()
-        .filter(|e| e.selected && e.issue == IssueType::Duplicate)
-        .map(|e| e.path.to_lowercase())
+/// Kürzt den PATH, ohne die Ordner zu ändern: spätere Duplikate fliegen raus und bekannte
+/// Präfixe werden durch Verweise wie `%LOCALAPPDATA%` (unter Unix `$HOME`) ersetzt.
+pub fn compact_path(current_path: &str) -> Compaction {
+    // Längste Werte zuerst, damit `%LOCALAPPDATA%` vor `%USERPROFILE%` greift
+    let mut vars: Vec<(String, String)> = COMPACT_VARS.iter()
+        .filter_map(|name| {
+            let value = std::env::var(name).ok()?;
+            let value = value.trim_end_matches(['/', '\\']).to_string();
+            let reference = if cfg!(windows) { format!("%{}%", name) } else { format!("${}", name) };
+            (value.len() > reference.len()).then_some((reference, value))
+        })
         .collect();
+    vars.sort_by_key(|(_, value)| std::cmp::Reverse(value.len()));
+    compact_with(current_path, &vars)
+}
 
-    let parts: Vec<&str> = current_path.split(';').filter(|s| !s.is_empty()).collect();
-    let mut new_parts = Vec::new();
+/// `compact_path` mit den Ersetzungen `vars` als (Verweis, Ordner), längste zuerst.
+fn compact_with(current_path: &str, vars: &[(String, String)]) -> Compaction {
     let mut seen = HashSet::new();
-    let mut removed_count = 0;
+    let mut duplicates = 0;
+    let mut substitutions: Vec<(String, String)> = Vec::new();
+    let mut parts = Vec::new();
 
-    for p in parts {
-        let p_lower = p.to_lowercase();
-        if to_remove_missing.contains(&p_lower) {
-            removed_count += 1;
+    for p in &PathList::parse(current_path) {
+        if !seen.insert(path_list::normalize(p)) {
+            duplicates += 1;
             continue;
         }
-        if to_deduplicate.contains(&p_lower) {
-            if seen.contains(&p_lower) {
-                removed_count += 1;
-                continue;
+        let substituted = vars.iter().find_map(|(reference, value)| {
+            replace_prefix(p, value).map(|rest| (reference, value, format!("{}{}", reference, rest)))
+        });
+        match substituted {
+            Some((reference, value, new)) => {
+                if !substitutions.iter().any(|(r, _)| r == reference) {
+                    substitutions.push((reference.clone(), value.clone()));
+                }
+                parts.push(new);
             }
+            None => parts.push(p.clone()),
+        }
+    }
+
+    let path = parts.into_iter().collect::<PathList>().to_string();
+    Compaction { path, duplicates, substitutions }
+}
+
+/// Rest von `entry` hinter `prefix`, wenn der Eintrag mit diesem Ordner beginnt (ohne Verweise).
+fn replace_prefix<'a>(entry: &'a str, prefix: &str) -> Option<&'a str> {
+    if entry.contains(['%', '$']) || entry.len() < prefix.len() {
+        return None;
+    }
+    let (head, rest) = entry.split_at_checked(prefix.len())?;
+    let matches = if cfg!(windows) { head.eq_ignore_ascii_case(prefix) } else { head == prefix };
+    (matches && (rest.is_empty() || rest.starts_with(['/', '\\']))).then_some(rest)
+}
+
+/// Behebt die ausgewählten Probleme in allen betroffenen Variablen und gibt die Anzahl
+/// geänderter Einträge zurück.
+pub fn run_cleaner(backend: &dyn EnvBackend, issues: &[CleanerEntry]) -> Result<usize, String> {
+    let mut vars: Vec<String> = Vec::new();
+    for e in issues {
+        if !vars.iter().any(|v| same_var(v, &e.var)) {
+            vars.push(e.var.clone());
+        }
+    }
+    let (planned, count) = cleanup_all(&read_lists(backend, &vars), issues);
+
+    if count > 0 {
+        write_lists(backend, &planned, "Cleanup")?;
+    }
+    Ok(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryBackend;
+    use crate::backend::testing::TestBackend;
+
+    fn backend(vars: &[(&str, &str)]) -> MemoryBackend {
+        MemoryBackend::with_vars(vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+    }
+
+    fn lists(values: &[(&str, &str)]) -> ListValues {
+        values.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    fn join(parts: &[&str]) -> String {
+        parts.iter().map(|p| p.to_string()).collect::<PathList>().to_string()
+    }
+
+    fn version(path: &str, alias: &str) -> VersionEntry {
+        VersionEntry { path: path.to_string(), alias: alias.to_string(), ..Default::default() }
+    }
+
+    /// `current` nach dem Umschalten auf node 20 mit `position`.
+    fn switched(position: InsertPosition, current: &[&str]) -> String {
+        let versions = vec![version("/opt/node18", "18"), version("/opt/node20", "20")];
+        let target = GroupTarget { position, ..Default::default() };
+        build_switched_list(&join(current), &versions, &versions[1], PATH_VAR, &target)
+    }
+
+    #[test]
+    fn insertion_index_follows_the_position() {
+        let parts = PathList::parse(&join(&["/a", "/b", "/c"]));
+        assert_eq!(insertion_index(&parts, &InsertPosition::Prepend, Some(1)), 0);
+        assert_eq!(insertion_index(&parts, &InsertPosition::Append, Some(1)), 3);
+        assert_eq!(insertion_index(&parts, &InsertPosition::Replace, Some(1)), 1);
+        assert_eq!(insertion_index(&parts, &InsertPosition::Replace, None), 0);
+        assert_eq!(insertion_index(&parts, &InsertPosition::Before("/b/".to_string()), None), 1);
+        assert_eq!(insertion_index(&parts, &InsertPosition::After("/b".to_string()), None), 2);
+        assert_eq!(insertion_index(&parts, &InsertPosition::Before("/missing".to_string()), None), 0);
+        assert_eq!(insertion_index(&parts, &InsertPosition::After("/missing".to_string()), None), 0);
+    }
+
+    #[test]
+    fn build_switched_list_prepends_and_appends() {
+        let current = ["/usr/bin", "/opt/node18", "/bin"];
+        assert_eq!(switched(InsertPosition::Prepend, &current), join(&["/opt/node20", "/usr/bin", "/bin"]));
+        assert_eq!(switched(InsertPosition::Append, &current), join(&["/usr/bin", "/bin", "/opt/node20"]));
+    }
+
+    #[test]
+    fn build_switched_list_replaces_the_previous_version() {
+        let current = ["/usr/bin", "/opt/node18", "/bin"];
+        assert_eq!(switched(InsertPosition::Replace, &current), join(&["/usr/bin", "/opt/node20", "/bin"]));
+        // Ohne bisherige Version nach vorne
+        assert_eq!(switched(InsertPosition::Replace, &["/usr/bin", "/bin"]), join(&["/opt/node20", "/usr/bin", "/bin"]));
+    }
+
+    #[test]
+    fn build_switched_list_uses_the_anchor() {
+        let current = ["/usr/bin", "/opt/node18", "/bin"];
+        assert_eq!(switched(InsertPosition::Before("/bin".to_string()), &current), join(&["/usr/bin", "/opt/node20", "/bin"]));
+        assert_eq!(switched(InsertPosition::After("/usr/bin".to_string()), &current), join(&["/usr/bin", "/opt/node20", "/bin"]));
+        assert_eq!(switched(InsertPosition::After("/bin".to_string()), &current), join(&["/usr/bin", "/bin", "/opt/node20"]));
+        // Fehlt der Anker, nach vorne
+        assert_eq!(switched(InsertPosition::Before("/missing".to_string()), &current), join(&["/opt/node20", "/usr/bin", "/bin"]));
+    }
+
+    #[test]
+    fn build_switched_list_keeps_extra_dirs_together() {
+        let mut python = version("/opt/py311", "3.11");
+        python.extra_paths.push("/opt/py311/scripts".to_string());
+        let versions = vec![version("/opt/py39", "3.9"), python];
+        let target = GroupTarget { position: InsertPosition::Replace, ..Default::default() };
+
+        let new = build_switched_list(&join(&["/usr/bin", "/opt/py39", "/bin"]), &versions, &versions[1], PATH_VAR, &target);
+
+        assert_eq!(new, join(&["/usr/bin", "/opt/py311", "/opt/py311/scripts", "/bin"]));
+    }
+
+    #[test]
+    fn activate_replaces_the_group_in_path() {
+        let backend = backend(&[(PATH_VAR, &join(&["/usr/bin", "/opt/node18"]))]);
+        let versions = vec![version("/opt/node18", "18"), version("/opt/node20", "20")];
+
+        activate(&backend, SwitchMode::Path, "node", &versions, &versions[1], &GroupTarget::default()).unwrap();
+
+        assert_eq!(get_current_path_var(&backend), join(&["/opt/node20", "/usr/bin"]));
+        assert_eq!(current_entry(SwitchMode::Path, &read_lists(&backend, &[PATH_VAR.to_string()]), "node", &versions, &GroupTarget::default())
+            .map(|v| v.alias.as_str()), Some("20"));
+    }
+
+    #[test]
+    fn activate_sets_the_variables_of_the_version() {
+        let backend = backend(&[(PATH_VAR, "")]);
+        let mut jdk = version("/opt/jdk17", "17");
+        jdk.env.insert("JAVA_HOME".to_string(), "${path}".to_string());
+
+        activate(&backend, SwitchMode::Path, "java", std::slice::from_ref(&jdk), &jdk, &GroupTarget::default()).unwrap();
+
+        assert_eq!(backend.read_var("JAVA_HOME").as_deref(), Some("/opt/jdk17"));
+        assert_eq!(get_current_path_var(&backend), "/opt/jdk17");
+    }
+
+    #[test]
+    fn write_lists_writes_changes_and_removes_empty_lists() {
+        let backend = backend(&[(PATH_VAR, "a"), ("PYTHONPATH", "p")]);
+        let planned = lists(&[(PATH_VAR, "b"), ("PYTHONPATH", ""), ("MANPATH", "m")]);
+
+        write_lists(&backend, &planned, "Test").unwrap();
+
+        assert_eq!(get_current_path_var(&backend), "b");
+        assert_eq!(backend.read_var("PYTHONPATH"), None);
+        assert_eq!(get_list_var(&backend, "MANPATH"), "m");
+    }
+
+    #[test]
+    fn perform_cleanup_fixes_only_selected_entries() {
+        let issue = |path: &str, issue: IssueType, selected: bool| CleanerEntry { var: PATH_VAR.to_string(), path: path.to_string(), issue, selected };
+        let current = join(&["/a", "/missing", "/a", "/kept"]);
+        let issues = vec![
+            issue("/missing", IssueType::Missing, true),
+            issue("/a", IssueType::Duplicate, true),
+            issue("/kept", IssueType::Missing, false),
+        ];
+
+        let (new, count) = perform_cleanup(PATH_VAR, &current, &issues);
+
+        assert_eq!(new, join(&["/a", "/kept"]));
+        assert_eq!(count, 2);
+        // Einträge anderer Variablen bleiben unberührt
+        assert_eq!(perform_cleanup("MANPATH", &current, &issues), (current.clone(), 0));
+    }
+
+    #[test]
+    fn run_cleaner_writes_the_cleaned_path() {
+        let existing = std::env::temp_dir().display().to_string();
+        let missing = std::env::temp_dir().join("version-switcher-test-missing").display().to_string();
+        let backend = backend(&[(PATH_VAR, &join(&[&existing, &missing, &existing]))]);
+
+        let issues = scan_for_issues(PATH_VAR, &get_current_path_var(&backend));
+        let count = run_cleaner(&backend, &issues).unwrap();
+
+        assert_eq!(count, 2);
+        assert_eq!(get_current_path_var(&backend), existing);
+    }
+
+    #[test]
+    fn write_lists_rolls_back_on_failed_write() {
+        let backend = TestBackend::failing_on(backend(&[(PATH_VAR, "a"), ("PYTHONPATH", "p")]), "PYTHONPATH");
+        let planned = lists(&[(PATH_VAR, "b"), ("PYTHONPATH", "q")]);
+
+        assert!(write_lists(&backend, &planned, "Test").is_err());
+        assert_eq!(get_current_path_var(&backend), "a");
+        assert_eq!(get_list_var(&backend, "PYTHONPATH"), "p");
+        assert_eq!(backend.broadcasts.get(), 0);
+    }
+
+    #[test]
+    fn failed_variable_write_leaves_path_unchanged() {
+        let backend = TestBackend::failing_on(backend(&[(PATH_VAR, "/opt/jdk8")]), "JAVA_HOME");
+        let mut jdk17 = version("/opt/jdk17", "17");
+        jdk17.env.insert("JAVA_HOME".to_string(), "${path}".to_string());
+        let versions = vec![version("/opt/jdk8", "8"), jdk17];
+
+        assert!(activate(&backend, SwitchMode::Path, "java", &versions, &versions[1], &GroupTarget::default()).is_err());
+        assert_eq!(get_current_path_var(&backend), "/opt/jdk8");
+        assert_eq!(backend.read_var("JAVA_HOME"), None);
+        assert_eq!(backend.broadcasts.get(), 0);
+    }
+
+    #[test]
+    fn activation_broadcasts_once() {
+        let backend = TestBackend::new(backend(&[(PATH_VAR, "/opt/jdk8"), ("CLASSPATH", "")]));
+        let mut jdk17 = version("/opt/jdk17", "17");
+        jdk17.env.insert("JAVA_HOME".to_string(), "${path}".to_string());
+        jdk17.lists.insert("CLASSPATH".to_string(), vec!["/opt/jdk17/lib".to_string()]);
+        let versions = vec![version("/opt/jdk8", "8"), jdk17];
+
+        activate(&backend, SwitchMode::Path, "java", &versions, &versions[1], &GroupTarget::default()).unwrap();
+
+        assert_eq!(get_current_path_var(&backend), "/opt/jdk17");
+        assert_eq!(get_list_var(&backend, "CLASSPATH"), "/opt/jdk17/lib");
+        assert_eq!(backend.read_var("JAVA_HOME").as_deref(), Some("/opt/jdk17"));
+        assert_eq!(backend.broadcasts.get(), 1);
+    }
+
+    #[test]
+    fn write_lists_writes_nothing_if_one_value_is_too_long() {
+        let backend = backend(&[(PATH_VAR, "a")]);
+        let planned = lists(&[(PATH_VAR, "b"), ("PYTHONPATH", &"x".repeat(PATH_HARD_LIMIT + 1))]);
+
+        assert!(write_lists(&backend, &planned, "Test").is_err());
+        assert_eq!(get_current_path_var(&backend), "a");
+        assert_eq!(backend.read_var("PYTHONPATH"), None);
+    }
+
+    /// Ordner `name` mit einer ausführbaren Datei `tool`.
+    fn tool_dir(parent: &Path, name: &str) -> String {
+        let dir = parent.join(name);
+        std::fs::create_dir(&dir).unwrap();
+        #[cfg(windows)]
+        std::fs::write(dir.join("tool.exe"), "").unwrap();
+        #[cfg(not(windows))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::write(dir.join("tool"), "#!/bin/sh\n").unwrap();
+            std::fs::set_permissions(dir.join("tool"), std::fs::Permissions::from_mode(0o755)).unwrap();
         }
-        seen.insert(p_lower);
-        new_parts.push(p);
+        dir.display().to_string()
+    }
+
+    #[test]
+    fn earlier_group_shadows_the_same_program_of_a_later_one() {
+        let tmp = tempfile::tempdir().unwrap();
+        let node = tool_dir(tmp.path(), "node20");
+        let python = tool_dir(tmp.path(), "py311");
+        let languages = HashMap::from([
+            ("NodeJS".to_string(), vec![version(&node, "20")]),
+            ("Python".to_string(), vec![version(&python, "3.11")]),
+        ]);
+        let values = lists(&[(PATH_VAR, &join(&[&python, &node]))]);
+
+        let conflicts = scan_for_conflicts(SwitchMode::Path, &values, &languages, &HashMap::new());
+
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].path, node);
+        assert_eq!(conflicts[0].issue, IssueType::Shadowed { group: "NodeJS".to_string(), by: "Python".to_string(), names: vec!["tool".to_string()] });
+        assert!(!conflicts[0].selected);
+    }
+
+    #[test]
+    fn groups_without_common_programs_do_not_conflict() {
+        let tmp = tempfile::tempdir().unwrap();
+        let node = tool_dir(tmp.path(), "node20");
+        let languages = HashMap::from([
+            ("NodeJS".to_string(), vec![version(&node, "20")]),
+            ("Empty".to_string(), vec![version(&tmp.path().display().to_string(), "1")]),
+        ]);
+        let values = lists(&[(PATH_VAR, &join(&[&tmp.path().display().to_string(), &node]))]);
+
+        assert!(scan_for_conflicts(SwitchMode::Path, &values, &languages, &HashMap::new()).is_empty());
+    }
+
+    #[test]
+    fn compact_path_drops_duplicates_and_shortens_prefixes() {
+        let vars = [("$HOME".to_string(), "/home/me".to_string())];
+        let current = join(&["/home/me/bin", "/usr/bin", "/home/me/bin/", "$HOME/.cargo/bin", "/home/me2/bin", "/usr/bin"]);
+
+        let compaction = compact_with(&current, &vars);
+
+        assert_eq!(compaction.path, join(&["$HOME/bin", "/usr/bin", "$HOME/.cargo/bin", "/home/me2/bin"]));
+        assert_eq!(compaction.duplicates, 2);
+        assert_eq!(compaction.substitutions, vars);
+        assert_eq!(compaction.saved(&current), current.len() - compaction.path.len());
+    }
+
+    #[test]
+    fn compact_path_keeps_a_clean_path() {
+        let current = join(&["/opt/a", "/opt/b"]);
+
+        let compaction = compact_path(&current);
+
+        assert_eq!(compaction.path, current);
+        assert_eq!(compaction.duplicates, 0);
+        assert_eq!(compaction.saved(&current), 0);
+    }
+
+    #[test]
+    fn version_with_missing_dirs_is_incomplete() {
+        let mut python = version("/opt/py311", "3.11");
+        python.extra_paths = vec!["/opt/py311/Scripts".to_string()];
+        let languages = HashMap::from([("Python".to_string(), vec![version("/opt/py310", "3.10"), python])]);
+        let values = lists(&[(PATH_VAR, &join(&["/opt/py311", "/usr/bin"]))]);
+
+        let entries = scan_for_incomplete(SwitchMode::Path, &values, &languages, &HashMap::new());
+
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].path, "/opt/py311");
+        assert_eq!(entries[0].issue, IssueType::Incomplete {
+            group: "Python".to_string(),
+            alias: "3.11".to_string(),
+            missing: vec!["/opt/py311/Scripts".to_string()],
+        });
+        assert!(entries[0].selected);
+        assert_eq!(entries[0].issue.fix(), Some(CleanerFix::InsertAfter(vec!["/opt/py311/Scripts".to_string()])));
     }
 
-    (new_parts.join(";"), removed_count)
-}
\ No newline at end of file
+    #[test]
+    fn complete_versions_and_shim_mode_report_nothing() {
+        let mut python = version("/opt/py311", "3.11");
+        python.extra_paths = vec!["/opt/py311/Scripts".to_string()];
+        let languages = HashMap::from([("Python".to_string(), vec![python])]);
+
+        let complete = lists(&[(PATH_VAR, &join(&["/opt/py311", "/opt/py311/Scripts"]))]);
+        assert!(scan_for_incomplete(SwitchMode::Path, &complete, &languages, &HashMap::new()).is_empty());
+
+        // Im Shim-Modus stehen die Ordner der Versionen nicht im PATH
+        let partial = lists(&[(PATH_VAR, "/opt/py311")]);
+        assert!(scan_for_incomplete(SwitchMode::Shims, &partial, &languages, &HashMap::new()).is_empty());
+    }
+}
@@ -1,16 +1,16 @@
 // Verstecke das Konsolenfenster im Release-Modus unter Windows
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
-// Module registrieren
-mod types;
-mod style;
-mod logic;
-mod language;
-mod app;
-
-use app::VersionSwitcherApp;
+use version_switcher::app::VersionSwitcherApp;
+use version_switcher::{cli, config};
 
 fn main() -> eframe::Result<()> {
+    // Mit Argumenten läuft das Programm ohne Fenster (z.B. in Skripten oder über SSH)
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if !args.is_empty() {
+        std::process::exit(cli::run(&args));
+    }
+
     let options = eframe::NativeOptions {
         viewport: eframe::egui::ViewportBuilder::default()
             .with_inner_size([650.0, 700.0]),
@@ -18,8 +18,8 @@ fn main() -> eframe::Result<()> {
     };
 
     eframe::run_native(
-        "Version Switcher",
+        config::APP_ID,
         options,
         Box::new(|cc| Ok(Box::new(VersionSwitcherApp::new(cc)))),
     )
-}
\ No newline at end of file
+}
@@ -0,0 +1,285 @@
+use std::fmt;
+
+/// Trennzeichen zwischen PATH-Einträgen auf dieser Plattform.
+#[cfg(windows)]
+pub const SEPARATOR: char = ';';
+#[cfg(not(windows))]
+pub const SEPARATOR: char = ':';
+
+/// Die Einträge eines PATH-Werts, so wie sie geschrieben wurden (ohne Anführungszeichen).
+///
+/// Unter Windows dürfen Einträge in `"..."` stehen und dann auch `;` enthalten.
+/// Leere Einträge werden beim Einlesen verworfen.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PathList {
+    entries: Vec<String>,
+}
+
+impl PathList {
+    pub fn parse(value: &str) -> Self {
+        let mut entries = Vec::new();
+        let mut current = String::new();
+        let mut quoted = false;
+
+        for c in value.chars() {
+            match c {
+                '"' if cfg!(windows) => quoted = !quoted,
+                c if c == SEPARATOR && !quoted => entries.push(std::mem::take(&mut current)),
+                c => current.push(c),
+            }
+        }
+        entries.push(current);
+        entries.retain(|e| !e.trim().is_empty());
+        Self { entries }
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    pub fn iter(&self) -> std::slice::Iter<'_, String> {
+        self.entries.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Position des ersten Eintrags, der auf denselben Ordner zeigt.
+    pub fn position(&self, entry: &str) -> Option<usize> {
+        let wanted = normalize(entry);
+        self.entries.iter().position(|e| normalize(e) == wanted)
+    }
+
+    pub fn contains(&self, entry: &str) -> bool {
+        self.position(entry).is_some()
+    }
+
+    pub fn insert(&mut self, index: usize, entry: impl Into<String>) {
+        self.entries.insert(index, entry.into());
+    }
+
+    pub fn push(&mut self, entry: impl Into<String>) {
+        self.entries.push(entry.into());
+    }
+
+    pub fn retain(&mut self, f: impl FnMut(&String) -> bool) {
+        self.entries.retain(f);
+    }
+
+    /// Entfernt alle Einträge, die auf denselben Ordner wie `entry` zeigen.
+    pub fn remove_all(&mut self, entry: &str) -> usize {
+        let wanted = normalize(entry);
+        let before = self.entries.len();
+        self.entries.retain(|e| normalize(e) != wanted);
+        before - self.entries.len()
+    }
+}
+
+impl fmt::Display for PathList {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (idx, entry) in self.entries.iter().enumerate() {
+            if idx > 0 {
+                write!(f, "{}", SEPARATOR)?;
+            }
+            if cfg!(windows) && entry.contains(SEPARATOR) {
+                write!(f, "\"{}\"", entry)?;
+            } else {
+                write!(f, "{}", entry)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl FromIterator<String> for PathList {
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        Self { entries: iter.into_iter().filter(|e| !e.trim().is_empty()).collect() }
+    }
+}
+
+impl<'a> IntoIterator for &'a PathList {
+    type Item = &'a String;
+    type IntoIter = std::slice::Iter<'a, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.entries.iter()
+    }
+}
+
+/// Form eines Eintrags zum Vergleichen: Variablen aufgelöst, ohne abschließenden Trenner,
+/// unter Windows außerdem mit `\` und ohne Groß-/Kleinschreibung.
+/// `C:\foo`, `c:/foo/` und `%SystemDrive%\foo` sind damit gleich.
+pub fn normalize(entry: &str) -> String {
+    let expanded = expand(entry.trim());
+    if cfg!(windows) {
+        let mut path = expanded.replace('/', "\\").to_lowercase();
+        // `C:\` bleibt, wie es ist, sonst würde daraus das aktuelle Verzeichnis auf C:
+        while path.ends_with('\\') && !(path.len() == 3 && path.as_bytes()[1] == b':') && path.len() > 1 {
+            path.pop();
+        }
+        path
+    } else {
+        let mut path = expanded;
+        while path.ends_with('/') && path.len() > 1 {
+            path.pop();
+        }
+        path
+    }
+}
+
+/// Ob zwei Einträge auf denselben Ordner zeigen.
+pub fn same_entry(a: &str, b: &str) -> bool {
+    a == b || normalize(a) == normalize(b)
+}
+
+/// Ersetzt Verweise auf andere Variablen durch ihren Wert, z.B. `%USERPROFILE%\bin`
+/// (unter Unix `$HOME/bin`, `${HOME}/bin` und `~/bin`). Unbekannte Variablen bleiben stehen.
+pub fn expand(entry: &str) -> String {
+    expand_collecting(entry).0
+}
+
+/// Namen der Variablen in `entry`, die sich nicht auflösen lassen (nicht gesetzt oder nicht geschlossen).
+pub fn unresolved_vars(entry: &str) -> Vec<String> {
+    expand_collecting(entry).1
+}
+
+fn expand_collecting(entry: &str) -> (String, Vec<String>) {
+    let mut missing = Vec::new();
+    if cfg!(windows) {
+        let mut result = String::new();
+        let mut rest = entry;
+        while let Some(start) = rest.find('%') {
+            let Some(len) = rest[start + 1..].find('%') else {
+                missing.push(rest[start + 1..].to_string());
+                break;
+            };
+            let name = &rest[start + 1..start + 1 + len];
+            result.push_str(&rest[..start]);
+            match std::env::var(name) {
+                Ok(value) if !name.is_empty() => result.push_str(&value),
+                _ => {
+                    missing.push(name.to_string());
+                    result.push_str(&rest[start..start + len + 2]);
+                }
+            }
+            rest = &rest[start + len + 2..];
+        }
+        result.push_str(rest);
+        (result, missing)
+    } else {
+        let home = std::env::var("HOME").unwrap_or_default();
+        let entry = match entry.strip_prefix('~') {
+            Some(tail) if tail.is_empty() || tail.starts_with('/') => format!("{}{}", home, tail),
+            _ => entry.to_string(),
+        };
+
+        let mut result = String::new();
+        let mut chars = entry.chars().peekable();
+        while let Some(c) = chars.next() {
+            if c != '$' {
+                result.push(c);
+                continue;
+            }
+            let braced = chars.next_if_eq(&'{').is_some();
+            let mut name = String::new();
+            while let Some(n) = chars.next_if(|n| n.is_ascii_alphanumeric() || *n == '_') {
+                name.push(n);
+            }
+            let closed = !braced || chars.next_if_eq(&'}').is_some();
+            match std::env::var(&name) {
+                Ok(value) if !name.is_empty() && closed => result.push_str(&value),
+                _ => {
+                    // Ein einzelnes `$` (z.B. in `a$`) ist kein Verweis
+                    if !name.is_empty() || braced {
+                        missing.push(name.clone());
+                    }
+                    result.push('$');
+                    if braced { result.push('{'); }
+                    result.push_str(&name);
+                    if braced && closed { result.push('}'); }
+                }
+            }
+        }
+        (result, missing)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn join(parts: &[&str]) -> String {
+        parts.join(&SEPARATOR.to_string())
+    }
+
+    #[test]
+    fn parse_drops_empty_entries_and_round_trips() {
+        let list = PathList::parse(&join(&["/a", "", " ", "/b c"]));
+        assert_eq!(list.entries(), ["/a", "/b c"]);
+        assert_eq!(PathList::parse(&list.to_string()), list);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn quoted_entries_keep_their_separator() {
+        let value = r#"C:\bin;"C:\odd;dir";C:\tools"#;
+        let list = PathList::parse(value);
+        assert_eq!(list.entries(), [r"C:\bin", r"C:\odd;dir", r"C:\tools"]);
+        assert_eq!(list.to_string(), value);
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn normalize_ignores_trailing_backslash_and_case() {
+        assert_eq!(normalize("C:\\foo\\"), normalize("C:\\foo"));
+        assert_eq!(normalize("c:/FOO"), normalize("C:\\foo"));
+        assert_eq!(normalize("C:\\"), "c:\\");
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn normalize_ignores_trailing_slash() {
+        assert_eq!(normalize("/opt/foo/"), normalize("/opt/foo"));
+        assert_eq!(normalize("/"), "/");
+        assert_ne!(normalize("/opt/Foo"), normalize("/opt/foo"));
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn expand_resolves_percent_references() {
+        let root = std::env::var("SystemRoot").unwrap();
+        assert_eq!(expand("%SystemRoot%\\System32"), format!("{}\\System32", root));
+        assert_eq!(expand("%VERSION_SWITCHER_UNSET%\\bin"), "%VERSION_SWITCHER_UNSET%\\bin");
+        assert_eq!(unresolved_vars("%VERSION_SWITCHER_UNSET%\\bin"), ["VERSION_SWITCHER_UNSET"]);
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn expand_resolves_dollar_references_and_tilde() {
+        let home = std::env::var("HOME").unwrap();
+        assert_eq!(expand("$HOME/bin"), format!("{}/bin", home));
+        assert_eq!(expand("${HOME}/bin"), format!("{}/bin", home));
+        assert_eq!(expand("~/bin"), format!("{}/bin", home));
+        assert_eq!(expand("~"), home);
+        assert_eq!(expand("~other/bin"), "~other/bin");
+        assert_eq!(expand("/opt/a$"), "/opt/a$");
+        assert_eq!(expand("$VERSION_SWITCHER_UNSET/bin"), "$VERSION_SWITCHER_UNSET/bin");
+        assert_eq!(unresolved_vars("${VERSION_SWITCHER_UNSET}/bin"), ["VERSION_SWITCHER_UNSET"]);
+    }
+
+    #[test]
+    fn same_entry_compares_expanded_entries() {
+        let (reference, expanded) = if cfg!(windows) {
+            ("%SystemRoot%", std::env::var("SystemRoot").unwrap())
+        } else {
+            ("$HOME", std::env::var("HOME").unwrap())
+        };
+        assert!(same_entry(reference, &expanded));
+        assert!(PathList::parse(&join(&["/x", &expanded])).contains(reference));
+    }
+}
@@ -0,0 +1,310 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::backend::EnvBackend;
+use crate::env_vars::EnvPlan;
+use crate::logic::{self, ListValues};
+use crate::shims;
+use crate::types::{GroupTarget, SwitchMode, VersionEntry};
+
+/// Eigene Projektdatei: ordnet Gruppen einen Alias zu, z.B. `Python = "3.11"`.
+pub const PIN_FILE: &str = ".versionswitcher.toml";
+
+/// Ein Eintrag aus einer Projektdatei: diese Gruppe soll mit diesem Alias laufen.
+#[derive(Clone, Debug)]
+pub struct Pin {
+    pub group: String,
+    pub alias: String,
+    /// Datei, aus der der Eintrag stammt.
+    pub source: PathBuf,
+}
+
+/// Warum ein Pin nicht aufgelöst werden konnte.
+#[derive(Clone, Debug)]
+pub enum PinProblem {
+    UnknownGroup(Pin),
+    UnknownAlias(Pin),
+}
+
+impl PinProblem {
+    pub fn describe(&self) -> String {
+        match self {
+            PinProblem::UnknownGroup(pin) => {
+                format!("Unknown group '{}' ({})", pin.group, pin.source.display())
+            }
+            PinProblem::UnknownAlias(pin) => {
+                format!("Unknown version '{}' in group {} ({})", pin.alias, pin.group, pin.source.display())
+            }
+        }
+    }
+}
+
+/// Ergebnis der Auflösung: gefundene Versionen pro Gruppe und alle Probleme.
+#[derive(Debug, Default)]
+pub struct PinResolution {
+    /// (Gruppe, Version) in der Reihenfolge der Gruppennamen.
+    pub resolved: Vec<(String, VersionEntry)>,
+    pub problems: Vec<PinProblem>,
+}
+
+/// Sucht ab `start` aufwärts nach Projektdateien.
+///
+/// Näher liegende Dateien gewinnen, und innerhalb eines Ordners hat `.versionswitcher.toml`
+/// Vorrang vor `.tool-versions`, `.python-version` und `.nvmrc`.
+pub fn find_pins(start: &Path) -> Result<Vec<Pin>, String> {
+    let mut pins: Vec<Pin> = Vec::new();
+    let mut dir = Some(start);
+
+    while let Some(d) = dir {
+        for pin in read_dir_pins(d)? {
+            if !pins.iter().any(|p| p.group.eq_ignore_ascii_case(&pin.group)) {
+                pins.push(pin);
+            }
+        }
+        dir = d.parent();
+    }
+    Ok(pins)
+}
+
+fn read_dir_pins(dir: &Path) -> Result<Vec<Pin>, String> {
+    let mut pins = Vec::new();
+
+    let own = dir.join(PIN_FILE);
+    if let Ok(content) = fs::read_to_string(&own) {
+        let table: BTreeMap<String, String> = toml::from_str(&content)
+            .map_err(|e| format!("{}: {}", own.display(), e))?;
+        for (group, alias) in table {
+            pins.push(Pin { group, alias, source: own.clone() });
+        }
+    }
+
+    // Fremde Formate werden nur gelesen, nie geschrieben
+    let tool_versions = dir.join(".tool-versions");
+    if let Ok(content) = fs::read_to_string(&tool_versions) {
+        for line in content.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let mut words = line.split_whitespace();
+            if let (Some(tool), Some(version)) = (words.next(), words.next()) {
+                pins.push(Pin { group: tool.to_string(), alias: version.to_string(), source: tool_versions.clone() });
+            }
+        }
+    }
+
+    for (file, group) in [(".python-version", "python"), (".nvmrc", "node")] {
+        let path = dir.join(file);
+        if let Ok(content) = fs::read_to_string(&path)
+            && let Some(version) = content.lines().map(str::trim).find(|l| !l.is_empty() && !l.starts_with('#'))
+        {
+            pins.push(Pin { group: group.to_string(), alias: version.to_string(), source: path.clone() });
+        }
+    }
+
+    Ok(pins)
+}
+
+/// Findet die Gruppe zu einem Pin: exakt, ohne Groß-/Kleinschreibung oder über bekannte Tool-Namen.
+fn find_group<'a>(languages: &'a HashMap<String, Vec<VersionEntry>>, name: &str) -> Option<&'a String> {
+    let candidates: &[&str] = match name.to_lowercase().as_str() {
+        "node" | "nodejs" => &["node", "nodejs", "node.js"],
+        "java" => &["java", "jdk"],
+        "golang" | "go" => &["go", "golang"],
+        _ => &[],
+    };
+
+    languages.keys().find(|k| k.as_str() == name)
+        .or_else(|| languages.keys().find(|k| k.eq_ignore_ascii_case(name)))
+        .or_else(|| languages.keys().find(|k| candidates.iter().any(|c| k.eq_ignore_ascii_case(c))))
+}
+
+/// Findet die Version zu einem Alias. `v20.1.0` aus `.nvmrc` passt auch auf `20.1.0`.
+fn find_alias<'a>(versions: &'a [VersionEntry], alias: &str) -> Option<&'a VersionEntry> {
+    let stripped = alias.strip_prefix('v').unwrap_or(alias);
+    versions.iter().find(|v| v.alias == alias)
+        .or_else(|| versions.iter().find(|v| v.alias.eq_ignore_ascii_case(alias)))
+        .or_else(|| versions.iter().find(|v| v.alias.strip_prefix('v').unwrap_or(&v.alias) == stripped))
+}
+
+/// Löst alle Pins gegen die konfigurierten Gruppen auf.
+pub fn resolve_pins(pins: &[Pin], languages: &HashMap<String, Vec<VersionEntry>>) -> PinResolution {
+    let mut resolution = PinResolution::default();
+
+    for pin in pins {
+        let Some(group) = find_group(languages, &pin.group) else {
+            resolution.problems.push(PinProblem::UnknownGroup(pin.clone()));
+            continue;
+        };
+        // z.B. `NodeJS` aus der eigenen Datei und `node` aus `.nvmrc`: der erste Pin gewinnt
+        if resolution.resolved.iter().any(|(g, _)| g == group) {
+            continue;
+        }
+        match find_alias(&languages[group], &pin.alias) {
+            Some(entry) => resolution.resolved.push((group.clone(), entry.clone())),
+            None => resolution.problems.push(PinProblem::UnknownAlias(pin.clone())),
+        }
+    }
+
+    resolution.resolved.sort_by(|a, b| a.0.cmp(&b.0));
+    resolution
+}
+
+/// Alle Variablen, die die aufgelösten Gruppen verwalten.
+pub fn resolution_vars(
+    resolution: &PinResolution,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+) -> Vec<String> {
+    let mut vars: Vec<String> = Vec::new();
+    for (group, _) in &resolution.resolved {
+        for var in logic::group_vars(&languages[group], &logic::group_target(targets, group)) {
+            if !vars.iter().any(|v| logic::same_var(v, &var)) {
+                vars.push(var);
+            }
+        }
+    }
+    vars
+}
+
+/// Die Werte, die `activate_resolution` schreiben würde. `values` muss die Variablen
+/// aus `resolution_vars` enthalten, `targets` sind Variable und Position der Gruppen.
+pub fn planned_lists(
+    values: &ListValues,
+    mode: SwitchMode,
+    resolution: &PinResolution,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+) -> Result<ListValues, String> {
+    let mut planned = values.clone();
+    // Rückwärts, damit die erste Gruppe am Ende ganz vorne steht
+    for (group, entry) in resolution.resolved.iter().rev() {
+        planned = logic::planned_lists(&planned, mode, &languages[group], entry, &logic::group_target(targets, group))?;
+    }
+    Ok(planned)
+}
+
+/// Aktiviert alle aufgelösten Versionen auf einmal: Listen, Variablen der Versionen und Shims
+/// werden zusammen geschrieben (siehe `logic::write_activation`), das System wird einmal
+/// benachrichtigt. `reason` landet in den Snapshots der alten Werte.
+pub fn activate_resolution(
+    backend: &dyn EnvBackend,
+    mode: SwitchMode,
+    resolution: &PinResolution,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+    reason: &str,
+) -> Result<(), String> {
+    let values = logic::read_lists(backend, &resolution_vars(resolution, languages, targets));
+    let planned = planned_lists(&values, mode, resolution, languages, targets)?;
+    let shim_plans: Vec<_> = resolution.resolved.iter()
+        .filter(|(group, _)| logic::mode_for(mode, &logic::group_target(targets, group)) == SwitchMode::Shims)
+        .map(|(group, entry)| shims::plan(group, entry))
+        .collect();
+    let mut env = EnvPlan::new(backend);
+    for (group, entry) in &resolution.resolved {
+        env.add(backend, &languages[group], entry);
+    }
+    logic::write_activation(backend, &planned, &env, &shim_plans, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryBackend;
+    use crate::backend::testing::TestBackend;
+    use crate::path_list::PathList;
+
+    fn version(path: &str, alias: &str) -> VersionEntry {
+        VersionEntry { path: path.to_string(), alias: alias.to_string(), ..Default::default() }
+    }
+
+    fn join(parts: &[&str]) -> String {
+        parts.iter().map(|p| p.to_string()).collect::<PathList>().to_string()
+    }
+
+    fn pin<'a>(pins: &'a [Pin], group: &str) -> &'a Pin {
+        pins.iter().find(|p| p.group == group).unwrap()
+    }
+
+    #[test]
+    fn nearer_files_win_while_walking_up() {
+        let root = tempfile::tempdir().unwrap();
+        let project = root.path().join("project");
+        let start = project.join("src");
+        fs::create_dir_all(&start).unwrap();
+        fs::write(root.path().join(".tool-versions"), "python 3.10.0\nnodejs 18 # LTS\n").unwrap();
+        fs::write(project.join(".python-version"), "\n3.11\n").unwrap();
+        fs::write(project.join(PIN_FILE), "Go = \"1.22\"\n").unwrap();
+
+        let pins = find_pins(&start).unwrap();
+
+        assert_eq!(pin(&pins, "Go").alias, "1.22");
+        assert_eq!(pin(&pins, "python").alias, "3.11");
+        assert_eq!(pin(&pins, "python").source, project.join(".python-version"));
+        assert_eq!(pin(&pins, "nodejs").alias, "18");
+        assert_eq!(pins.len(), 3);
+    }
+
+    #[test]
+    fn own_file_wins_within_a_folder() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(PIN_FILE), "python = \"3.12\"\n").unwrap();
+        fs::write(dir.path().join(".python-version"), "3.11\n").unwrap();
+        fs::write(dir.path().join(".nvmrc"), "# Node\nv20.1.0\n").unwrap();
+
+        let pins = find_pins(dir.path()).unwrap();
+
+        assert_eq!(pin(&pins, "python").alias, "3.12");
+        assert_eq!(pin(&pins, "node").alias, "v20.1.0");
+    }
+
+    #[test]
+    fn broken_own_file_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(PIN_FILE), "python = 3.12 = x\n").unwrap();
+
+        assert!(find_pins(dir.path()).is_err());
+    }
+
+    #[test]
+    fn resolves_tool_names_and_reports_unknown_pins() {
+        let languages = HashMap::from([
+            ("NodeJS".to_string(), vec![version("/opt/node18", "18"), version("/opt/node20", "20.1.0")]),
+            ("Python".to_string(), vec![version("/opt/py311", "3.11")]),
+        ]);
+        let pins: Vec<Pin> = [("node", "v20.1.0"), ("python", "3.9"), ("ruby", "3.3")].iter()
+            .map(|(group, alias)| Pin { group: group.to_string(), alias: alias.to_string(), source: PathBuf::from(".tool-versions") })
+            .collect();
+
+        let resolution = resolve_pins(&pins, &languages);
+
+        assert_eq!(resolution.resolved.len(), 1);
+        assert_eq!(resolution.resolved[0].0, "NodeJS");
+        assert_eq!(resolution.resolved[0].1.path, "/opt/node20");
+        assert!(matches!(&resolution.problems[..], [PinProblem::UnknownAlias(_), PinProblem::UnknownGroup(_)]));
+    }
+
+    #[test]
+    fn activating_a_resolution_writes_all_groups_at_once() {
+        let backend = TestBackend::new(MemoryBackend::with_vars(
+            [(logic::PATH_VAR.to_string(), join(&["/opt/node18", "/opt/py310", "/usr/bin"]))].into(),
+        ));
+        let mut go = version("/opt/go/bin", "1.22");
+        go.env.insert("GOROOT".to_string(), "/opt/go".to_string());
+        let languages = HashMap::from([
+            ("NodeJS".to_string(), vec![version("/opt/node18", "18"), version("/opt/node20", "20")]),
+            ("Python".to_string(), vec![version("/opt/py310", "3.10"), version("/opt/py311", "3.11")]),
+            ("Go".to_string(), vec![go]),
+        ]);
+        let pins: Vec<Pin> = [("python", "3.11"), ("node", "20"), ("go", "1.22")].iter()
+            .map(|(group, alias)| Pin { group: group.to_string(), alias: alias.to_string(), source: PathBuf::from(PIN_FILE) })
+            .collect();
+        let resolution = resolve_pins(&pins, &languages);
+
+        activate_resolution(&backend, SwitchMode::Path, &resolution, &languages, &HashMap::new(), "Project").unwrap();
+
+        // Gruppen in alphabetischer Reihenfolge vorne, die alten Versionen ersetzt
+        assert_eq!(logic::get_current_path_var(&backend), join(&["/opt/go/bin", "/opt/node20", "/opt/py311", "/usr/bin"]));
+        assert_eq!(backend.read_var("GOROOT").as_deref(), Some("/opt/go"));
+        assert_eq!(backend.broadcasts.get(), 1);
+    }
+}
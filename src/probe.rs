@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, ExitStatus, Stdio};
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+use std::time::{Duration, Instant, UNIX_EPOCH};
+
+use crate::logic;
+use crate::path_list;
+
+/// Wie die echte Version einer Gruppe ermittelt wird, z.B. `python --version`.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+#[serde(default)]
+pub struct Probe {
+    /// Programmname ohne Endung, wird im Ordner der Version gesucht.
+    pub executable: String,
+    /// Argumente, durch Leerzeichen getrennt.
+    pub args: String,
+    /// Regulärer Ausdruck, die erste Gruppe (oder der ganze Treffer) ist die Version.
+    pub regex: String,
+}
+
+impl Default for Probe {
+    fn default() -> Self {
+        Self {
+            executable: String::new(),
+            args: "--version".to_owned(),
+            regex: r"(\d+(?:\.\d+)+)".to_owned(),
+        }
+    }
+}
+
+/// Ergebnis einer Abfrage, gültig solange sich der Ordner nicht ändert.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct ProbeResult {
+    /// Änderungszeit des Ordners (Sekunden seit 1970) zum Zeitpunkt der Abfrage.
+    pub mtime: u64,
+    pub version: Option<String>,
+    pub error: Option<String>,
+}
+
+const PROBE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Änderungszeit eines Ordners in Sekunden.
+pub fn dir_mtime(path: &Path) -> Option<u64> {
+    let modified = path.metadata().ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs())
+}
+
+/// Prüft, ob ein gespeichertes Ergebnis noch zum Ordner passt (Verweise wie `%VAR%` werden aufgelöst).
+pub fn is_fresh(result: &ProbeResult, dir: &str) -> bool {
+    // Fehlende Ordner zählen als 0, sonst würden sie endlos neu abgefragt
+    dir_mtime(Path::new(&path_list::expand(dir))).unwrap_or_default() == result.mtime
+}
+
+/// Ob die erkannte Version nicht zum Alias passt. `3.11` passt zu `3.11.4`, aber nicht zu `3.9.7`.
+/// Enthält der Alias keine Versionsnummer, gibt es keine Warnung.
+pub fn is_mismatch(alias: &str, detected: &str) -> bool {
+    let Some(declared) = logic::extract_version(alias) else { return false };
+    detected != declared && !detected.starts_with(&format!("{}.", declared))
+}
+
+fn find_executable(dir: &Path, name: &str) -> Option<std::path::PathBuf> {
+    let candidates: Vec<String> = if cfg!(windows) {
+        ["exe", "cmd", "bat", "com"].iter().map(|ext| format!("{}.{}", name, ext)).collect()
+    } else {
+        vec![name.to_string()]
+    };
+    candidates.into_iter().map(|c| dir.join(c)).find(|p| logic::is_executable(p))
+}
+
+/// Führt die Abfrage für einen Ordner aus (blockiert, daher nicht im UI-Thread aufrufen).
+pub fn run_probe(probe: &Probe, dir: &str) -> ProbeResult {
+    let dir = path_list::expand(dir);
+    let mtime = dir_mtime(Path::new(&dir)).unwrap_or_default();
+    let fail = |error: String| ProbeResult { mtime, version: None, error: Some(error) };
+
+    let regex = match regex::Regex::new(&probe.regex) {
+        Ok(r) => r,
+        Err(e) => return fail(e.to_string()),
+    };
+    let Some(exe) = find_executable(Path::new(&dir), &probe.executable) else {
+        return fail(format!("{} not found", probe.executable));
+    };
+
+    let mut cmd = Command::new(&exe);
+    cmd.args(probe.args.split_whitespace())
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    #[cfg(windows)]
+    {
+        // Kein Konsolenfenster aufblitzen lassen
+        use std::os::windows::process::CommandExt;
+        cmd.creation_flags(0x08000000);
+    }
+
+    let output = match run_with_timeout(cmd, PROBE_TIMEOUT) {
+        Ok((_, o)) => o,
+        Err(e) => return fail(e),
+    };
+
+    // z.B. `java -version` schreibt auf stderr
+    match regex.captures(&output) {
+        Some(caps) => {
+            let m = caps.get(1).or_else(|| caps.get(0)).map(|m| m.as_str().to_string());
+            ProbeResult { mtime, version: m, error: None }
+        }
+        None => fail(format!("No version in output: {}", output.trim())),
+    }
+}
+
+/// Startet `cmd` und wartet höchstens `timeout`. Liefert den Exit-Status und stdout + stderr.
+/// `cmd` muss stdout und stderr auf `Stdio::piped()` setzen.
+pub fn run_with_timeout(mut cmd: Command, timeout: Duration) -> Result<(ExitStatus, String), String> {
+    let mut child = cmd.spawn().map_err(|e| e.to_string())?;
+    // Ausgaben nebenher lesen, damit das Programm nicht an einer vollen Pipe hängen bleibt
+    let stdout = child.stdout.take().map(read_in_background);
+    let stderr = child.stderr.take().map(read_in_background);
+    let start = Instant::now();
+
+    let status = loop {
+        match child.try_wait().map_err(|e| e.to_string())? {
+            Some(status) => break status,
+            None if start.elapsed() > timeout => {
+                let _ = child.kill();
+                let _ = child.wait();
+                return Err("Timeout".to_string());
+            }
+            None => thread::sleep(Duration::from_millis(20)),
+        }
+    };
+
+    let mut output = String::new();
+    for reader in [stdout, stderr].into_iter().flatten() {
+        output.push_str(&reader.join().unwrap_or_default());
+    }
+    Ok((status, output))
+}
+
+fn read_in_background(mut pipe: impl Read + Send + 'static) -> thread::JoinHandle<String> {
+    thread::spawn(move || {
+        let mut text = String::new();
+        let _ = pipe.read_to_string(&mut text);
+        text
+    })
+}
+
+/// Startet die Abfragen in einem Hintergrund-Thread. Ergebnisse kommen als (Ordner, Ergebnis).
+pub fn spawn_probes(jobs: Vec<(String, Probe)>) -> Receiver<(String, ProbeResult)> {
+    let (tx, rx) = mpsc::channel();
+    thread::spawn(move || {
+        for (dir, probe) in jobs {
+            let result = run_probe(&probe, &dir);
+            if tx.send((dir, result)).is_err() {
+                break;
+            }
+        }
+    });
+    rx
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_fresh_resolves_references() {
+        let dir = if cfg!(windows) { "%USERPROFILE%" } else { "$HOME" };
+        let mtime = dir_mtime(Path::new(&path_list::expand(dir))).unwrap();
+        assert_ne!(mtime, 0);
+
+        assert!(is_fresh(&ProbeResult { mtime, version: None, error: None }, dir));
+        assert!(!is_fresh(&ProbeResult { mtime: mtime + 1, version: None, error: None }, dir));
+    }
+
+    #[test]
+    fn mismatch_compares_version_prefixes() {
+        assert!(!is_mismatch("Python 3.11", "3.11.4"));
+        assert!(is_mismatch("Python 3.11", "3.9.7"));
+        assert!(!is_mismatch("latest", "3.9.7"));
+    }
+}
@@ -0,0 +1,96 @@
+use std::collections::{BTreeMap, HashMap};
+
+use crate::backend::EnvBackend;
+use crate::logic::{self, ListValues};
+use crate::pins::{self, PinResolution};
+use crate::types::{GroupTarget, SwitchMode, VersionEntry};
+
+/// Ein benanntes Set aus je einer Version pro Gruppe (Gruppe -> Alias),
+/// z.B. Python 3.11 + NodeJS 20 + Java 17.
+pub type Profile = BTreeMap<String, String>;
+
+/// Löst ein Profil auf. Fehlt eine Gruppe oder Version, wird gar nichts aktiviert.
+pub fn resolve(profile: &Profile, languages: &HashMap<String, Vec<VersionEntry>>) -> Result<PinResolution, String> {
+    let mut resolution = PinResolution::default();
+    let mut missing = Vec::new();
+
+    for (group, alias) in profile {
+        match languages.get(group).and_then(|versions| versions.iter().find(|v| &v.alias == alias)) {
+            Some(entry) => resolution.resolved.push((group.clone(), entry.clone())),
+            None => missing.push(format!("{} {}", group, alias)),
+        }
+    }
+    if !missing.is_empty() {
+        return Err(format!("Unknown versions in profile: {}", missing.join(", ")));
+    }
+    Ok(resolution)
+}
+
+/// Ob jede Gruppe des Profils gerade auf der Version des Profils steht.
+/// `values` muss die Variablen der Gruppen enthalten (z.B. `Config::managed_vars`).
+pub fn matches(
+    profile: &Profile,
+    mode: SwitchMode,
+    values: &ListValues,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+) -> bool {
+    !profile.is_empty() && profile.iter().all(|(group, alias)| {
+        languages.get(group)
+            .and_then(|versions| logic::current_entry(mode, values, group, versions, &logic::group_target(targets, group)))
+            .is_some_and(|entry| &entry.alias == alias)
+    })
+}
+
+/// Namen aller Profile, die zum aktuellen Zustand passen.
+pub fn matching(
+    profiles: &BTreeMap<String, Profile>,
+    mode: SwitchMode,
+    values: &ListValues,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+) -> Vec<String> {
+    profiles.iter()
+        .filter(|(_, profile)| matches(profile, mode, values, languages, targets))
+        .map(|(name, _)| name.clone())
+        .collect()
+}
+
+/// Profil aus den gerade aktiven Versionen aller Gruppen.
+pub fn from_current(
+    mode: SwitchMode,
+    values: &ListValues,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+) -> Profile {
+    languages.iter()
+        .filter_map(|(group, versions)| {
+            logic::current_entry(mode, values, group, versions, &logic::group_target(targets, group))
+                .map(|entry| (group.clone(), entry.alias.clone()))
+        })
+        .collect()
+}
+
+/// Die Werte, die `activate` schreiben würde (siehe `pins::planned_lists`).
+pub fn planned_lists(
+    values: &ListValues,
+    mode: SwitchMode,
+    profile: &Profile,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+) -> Result<ListValues, String> {
+    pins::planned_lists(values, mode, &resolve(profile, languages)?, languages, targets)
+}
+
+/// Aktiviert alle Versionen des Profils `name` mit einem Schreibvorgang pro Variable.
+pub fn activate(
+    backend: &dyn EnvBackend,
+    mode: SwitchMode,
+    name: &str,
+    profile: &Profile,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    targets: &HashMap<String, GroupTarget>,
+) -> Result<(), String> {
+    let resolution = resolve(profile, languages)?;
+    pins::activate_resolution(backend, mode, &resolution, languages, targets, &format!("Profile {}", name))
+}
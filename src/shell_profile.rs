@@ -0,0 +1,432 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+
+use crate::backend::EnvBackend;
+use crate::path_list::PathList;
+
+// Markierungen für den Block, der diesem Tool gehört. Alles außerhalb bleibt unangetastet.
+const BLOCK_START: &str = "# >>> version_switcher >>>";
+const BLOCK_END: &str = "# <<< version_switcher <<<";
+const BLOCK_NOTE: &str = "# Managed by version_switcher. Changes inside this block will be overwritten.";
+/// Merkt sich in der Shell, welche Einträge schon vor den PATH gesetzt wurden. Verschachtelte
+/// Shells (oder `.profile` und danach `.bashrc`) setzen sie so nicht noch einmal davor.
+const PATH_SENTINEL: &str = "VERSION_SWITCHER_PATH";
+
+/// Ein Wert im Block. Wörtliche Werte stehen in einfachen Anführungszeichen,
+/// darin löst die Shell keine Verweise wie `$HOME` auf.
+struct Value {
+    text: String,
+    literal: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Shell {
+    Posix,
+    Fish,
+}
+
+/// Verwaltet Variablen in einem eigenen Block in den Shell-Startdateien (Linux/Unix).
+///
+/// `PATH` wird wie der Benutzer-PATH unter Windows behandelt: Der Block enthält nur die
+/// Einträge des Benutzers, die vor den PATH des Systems gesetzt werden.
+/// Alle anderen Variablen werden direkt exportiert.
+pub struct ShellProfileBackend {
+    home: PathBuf,
+}
+
+impl ShellProfileBackend {
+    pub fn new(home: impl Into<PathBuf>) -> Self {
+        Self { home: home.into() }
+    }
+
+    /// `~/.profile` ist die Quelle der Wahrheit, aus der gelesen wird.
+    fn primary_file(&self) -> PathBuf {
+        self.home.join(".profile")
+    }
+
+    /// Alle Dateien, in die der Block geschrieben wird.
+    /// `.bashrc`, `.zshrc` und fish werden nur angefasst, wenn die Shell eingerichtet ist.
+    fn targets(&self) -> Vec<(PathBuf, Shell)> {
+        let mut targets = vec![(self.primary_file(), Shell::Posix)];
+        for rc in [".bashrc", ".zshrc"] {
+            let path = self.home.join(rc);
+            if path.exists() {
+                targets.push((path, Shell::Posix));
+            }
+        }
+        let fish_dir = self.home.join(".config").join("fish");
+        if fish_dir.is_dir() {
+            targets.push((fish_dir.join("conf.d").join("version_switcher.fish"), Shell::Fish));
+        }
+        targets
+    }
+
+    fn load(&self) -> BTreeMap<String, Value> {
+        let content = fs::read_to_string(self.primary_file()).unwrap_or_default();
+        let mut vars = BTreeMap::new();
+        if let Some((start, end)) = find_block(&content) {
+            for line in content.lines().skip(start + 1).take(end - start - 1) {
+                if let Some((name, value)) = parse_posix_line(line) {
+                    vars.insert(name, value);
+                }
+            }
+        }
+        vars
+    }
+
+    fn store(&self, vars: &BTreeMap<String, Value>) -> Result<(), String> {
+        for (path, shell) in self.targets() {
+            let content = fs::read_to_string(&path).unwrap_or_default();
+            let block = if vars.is_empty() { None } else { Some(render_block(vars, shell)) };
+            let new_content = replace_block(&content, block.as_deref());
+            if new_content == content {
+                continue;
+            }
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent).map_err(|e| format!("{}: {}", path.display(), e))?;
+            }
+            fs::write(&path, new_content).map_err(|e| format!("{}: {}", path.display(), e))?;
+        }
+        Ok(())
+    }
+}
+
+impl EnvBackend for ShellProfileBackend {
+    fn read_var(&self, name: &str) -> Option<String> {
+        self.load().remove(name).map(|v| v.text)
+    }
+
+    // Die Art eines vorhandenen Werts bleibt erhalten, neue Werte sind erweiterbar
+    fn write_var(&self, name: &str, value: &str) -> Result<(), String> {
+        let expandable = self.load().get(name).is_none_or(|v| !v.literal);
+        self.write_var_as(name, value, expandable)
+    }
+
+    fn write_var_as(&self, name: &str, value: &str, expandable: bool) -> Result<(), String> {
+        if !is_valid_name(name) {
+            return Err(format!("Invalid variable name: {}", name));
+        }
+        let mut vars = self.load();
+        // PATH hängt immer den PATH des Systems an und ist daher nie wörtlich
+        let literal = !expandable && name != "PATH";
+        vars.insert(name.to_string(), Value { text: value.to_string(), literal });
+        self.store(&vars)
+    }
+
+    fn var_names(&self) -> Vec<String> {
+        self.load().into_keys().collect()
+    }
+
+    fn is_expandable(&self, name: &str) -> bool {
+        self.load().get(name).is_some_and(|v| !v.literal)
+    }
+
+    fn remove_var(&self, name: &str) -> Result<(), String> {
+        let mut vars = self.load();
+        if vars.remove(name).is_some() {
+            self.store(&vars)?;
+        }
+        Ok(())
+    }
+
+    // Ein `~` am Anfang eines Eintrags wird als `$HOME` geschrieben (siehe `render_block`)
+    fn stored_value(&self, name: &str, value: &str) -> String {
+        let literal = name != "PATH" && self.load().get(name).is_some_and(|v| v.literal);
+        if literal { value.to_string() } else { tilde_to_home(value) }
+    }
+
+    // Neue Shells lesen die Dateien beim Start, eine Benachrichtigung gibt es nicht.
+    fn broadcast_change(&self) {}
+}
+
+fn is_valid_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Liefert die Zeilenindizes von Start- und Endmarkierung.
+fn find_block(content: &str) -> Option<(usize, usize)> {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.iter().position(|l| l.trim() == BLOCK_START)?;
+    let end = lines.iter().skip(start + 1).position(|l| l.trim() == BLOCK_END)? + start + 1;
+    Some((start, end))
+}
+
+/// Ersetzt den vorhandenen Block (oder hängt ihn an). Mit `None` wird der Block entfernt.
+fn replace_block(content: &str, block: Option<&str>) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut out: Vec<String> = Vec::new();
+
+    match find_block(content) {
+        Some((start, end)) => {
+            out.extend(lines[..start].iter().map(|l| l.to_string()));
+            if let Some(b) = block {
+                out.push(b.to_string());
+            }
+            out.extend(lines[end + 1..].iter().map(|l| l.to_string()));
+        }
+        None => {
+            out.extend(lines.iter().map(|l| l.to_string()));
+            if let Some(b) = block {
+                if out.last().is_some_and(|l| !l.trim().is_empty()) {
+                    out.push(String::new());
+                }
+                out.push(b.to_string());
+            }
+        }
+    }
+
+    if out.is_empty() {
+        return String::new();
+    }
+    out.join("\n") + "\n"
+}
+
+fn render_block(vars: &BTreeMap<String, Value>, shell: Shell) -> String {
+    let mut lines = vec![BLOCK_START.to_string(), BLOCK_NOTE.to_string()];
+    for (name, Value { text: value, literal }) in vars {
+        // Ein leerer Eintrag würde sonst das aktuelle Verzeichnis in den PATH holen
+        if name == "PATH" && value.is_empty() {
+            continue;
+        }
+        let line = match shell {
+            Shell::Posix if *literal => format!("export {}='{}'", name, value.replace('\'', "'\\''")),
+            Shell::Fish if *literal => format!("set -gx {} {}", name, quote_fish_literal(value)),
+            Shell::Posix if name == "PATH" => {
+                let value = escape_posix(&tilde_to_home(value));
+                [
+                    format!("if [ \"${{{}:-}}\" != \"{}\" ]; then", PATH_SENTINEL, value),
+                    format!("    export PATH=\"{}:$PATH\"", value),
+                    format!("    export {}=\"{}\"", PATH_SENTINEL, value),
+                    "fi".to_string(),
+                ].join("\n")
+            }
+            Shell::Posix => format!("export {}=\"{}\"", name, escape_posix(&tilde_to_home(value))),
+            Shell::Fish if name == "PATH" => {
+                let value = tilde_to_home(value);
+                let parts: Vec<String> = PathList::parse(&value).iter()
+                    .map(|p| quote_fish(p))
+                    .collect();
+                [
+                    format!("if test \"${}\" != {}", PATH_SENTINEL, quote_fish(&value)),
+                    format!("    set -gx PATH {} $PATH", parts.join(" ")),
+                    format!("    set -gx {} {}", PATH_SENTINEL, quote_fish(&value)),
+                    "end".to_string(),
+                ].join("\n")
+            }
+            Shell::Fish => format!("set -gx {} {}", name, quote_fish(&tilde_to_home(value))),
+        };
+        lines.push(line);
+    }
+    lines.push(BLOCK_END.to_string());
+    lines.join("\n")
+}
+
+fn parse_posix_line(line: &str) -> Option<(String, Value)> {
+    let (name, raw) = line.trim().strip_prefix("export ")?.split_once('=')?;
+    if name == PATH_SENTINEL {
+        return None;
+    }
+    if let Some(raw) = raw.strip_prefix('\'').and_then(|r| r.strip_suffix('\'')) {
+        let text = raw.replace("'\\''", "'");
+        return Some((name.to_string(), Value { text, literal: true }));
+    }
+    let raw = raw.strip_prefix('"')?.strip_suffix('"')?;
+    let raw = if name == "PATH" { raw.strip_suffix(":$PATH")? } else { raw };
+    Some((name.to_string(), Value { text: unescape_posix(raw), literal: false }))
+}
+
+/// In Anführungszeichen löst die Shell `~` nicht auf, daher wird ein `~` am Anfang
+/// eines Eintrags zu `$HOME`.
+fn tilde_to_home(value: &str) -> String {
+    value.split(':')
+        .map(|part| match part.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("$HOME{}", rest),
+            _ => part.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(":")
+}
+
+// In doppelten Anführungszeichen haben nur \ " $ und ` eine Sonderbedeutung.
+// Verweise wie `$HOME` oder `${HOME}` bleiben stehen, damit die Shell sie auflöst.
+fn escape_posix(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    for segment in split_var_refs(value) {
+        match segment {
+            Segment::Text(text) => {
+                for c in text.chars() {
+                    if matches!(c, '\\' | '"' | '$' | '`') {
+                        out.push('\\');
+                    }
+                    out.push(c);
+                }
+            }
+            Segment::Var { raw, .. } => out.push_str(&raw),
+        }
+    }
+    out
+}
+
+enum Segment {
+    Text(String),
+    Var { name: String, raw: String },
+}
+
+/// Zerlegt einen Wert in Text und Verweise auf Variablen (`$NAME` oder `${NAME}`).
+fn split_var_refs(value: &str) -> Vec<Segment> {
+    let mut segments = Vec::new();
+    let mut text = String::new();
+    let mut rest = value;
+
+    while let Some(pos) = rest.find('$') {
+        text.push_str(&rest[..pos]);
+        let after = &rest[pos + 1..];
+        let (name, raw_len) = match after.strip_prefix('{') {
+            Some(inner) => match inner.find('}') {
+                Some(end) if is_var_name(&inner[..end]) => (&inner[..end], end + 3),
+                _ => ("", 0),
+            },
+            None => {
+                let len = after.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(after.len());
+                (&after[..len], len + 1)
+            }
+        };
+        if is_var_name(name) {
+            if !text.is_empty() {
+                segments.push(Segment::Text(std::mem::take(&mut text)));
+            }
+            segments.push(Segment::Var { name: name.to_string(), raw: rest[pos..pos + raw_len].to_string() });
+            rest = &rest[pos + raw_len..];
+        } else {
+            text.push('$');
+            rest = after;
+        }
+    }
+    text.push_str(rest);
+    if !text.is_empty() {
+        segments.push(Segment::Text(text));
+    }
+    segments
+}
+
+fn is_var_name(name: &str) -> bool {
+    name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn unescape_posix(raw: &str) -> String {
+    let mut out = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            if let Some(next) = chars.next() {
+                out.push(next);
+            }
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+fn quote_fish(value: &str) -> String {
+    let segments = split_var_refs(value);
+    if !segments.iter().any(|s| matches!(s, Segment::Var { .. })) {
+        return quote_fish_literal(value);
+    }
+
+    // Mit Verweisen in doppelten Anführungszeichen. fish kennt kein `${NAME}`, daher wird
+    // nach jedem Verweis neu angesetzt (`"$HOME""/bin"`), damit der Name dort endet.
+    let mut out = String::from("\"");
+    for segment in segments {
+        match segment {
+            Segment::Text(text) => {
+                for c in text.chars() {
+                    if matches!(c, '\\' | '"' | '$') {
+                        out.push('\\');
+                    }
+                    out.push(c);
+                }
+            }
+            Segment::Var { name, .. } => {
+                out.push('$');
+                out.push_str(&name);
+                out.push_str("\"\"");
+            }
+        }
+    }
+    out.push('"');
+    out
+}
+
+fn quote_fish_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Backend für das Home-Verzeichnis des aktuellen Benutzers.
+pub fn for_current_user() -> ShellProfileBackend {
+    let home = std::env::var_os("HOME").map(PathBuf::from).unwrap_or_default();
+    ShellProfileBackend::new(home)
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tilde_becomes_home() {
+        assert_eq!(tilde_to_home("~/bin:/usr/bin:~"), "$HOME/bin:/usr/bin:$HOME");
+        assert_eq!(tilde_to_home("~other/bin:/a~b"), "~other/bin:/a~b");
+    }
+
+    #[test]
+    fn stored_value_matches_what_is_read_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path();
+        let backend = ShellProfileBackend::new(home);
+        backend.write_var("PATH", "~/bin:/opt").unwrap();
+        backend.write_var_as("NOTES", "~/notes", false).unwrap();
+
+        assert_eq!(backend.read_var("PATH"), Some(backend.stored_value("PATH", "~/bin:/opt")));
+        assert_eq!(backend.read_var("NOTES"), Some(backend.stored_value("NOTES", "~/notes")));
+    }
+
+    #[test]
+    fn path_round_trip_hides_the_sentinel() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path();
+        let backend = ShellProfileBackend::new(home);
+        backend.write_var("PATH", "/opt/a b:$HOME/bin").unwrap();
+        backend.write_var_as("GREETING", "it's $5", false).unwrap();
+
+        assert_eq!(backend.read_var("PATH").as_deref(), Some("/opt/a b:$HOME/bin"));
+        assert_eq!(backend.read_var("GREETING").as_deref(), Some("it's $5"));
+        assert_eq!(backend.var_names(), vec!["GREETING".to_string(), "PATH".to_string()]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn nested_shells_do_not_prepend_path_twice() {
+        let dir = tempfile::tempdir().unwrap();
+        let home = dir.path();
+        fs::write(home.join(".bashrc"), "").unwrap();
+        let backend = ShellProfileBackend::new(home);
+        backend.write_var("PATH", "~/bin").unwrap();
+
+        let script = ". ./.profile; . ./.bashrc; sh -c '. ./.profile; printf %s \"$PATH\"'";
+        let output = std::process::Command::new("sh")
+            .args(["-c", script])
+            .current_dir(home)
+            .env("HOME", home)
+            .env("PATH", "/usr/bin:/bin")
+            .env_remove(PATH_SENTINEL)
+            .output()
+            .unwrap();
+
+        assert_eq!(String::from_utf8_lossy(&output.stdout), format!("{}/bin:/usr/bin:/bin", home.display()));
+    }
+}
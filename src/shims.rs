@@ -0,0 +1,307 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::config;
+use crate::logic;
+use crate::path_list::{self, PathList};
+use crate::types::{InsertPosition, VersionEntry};
+
+/// Merkt sich, welche Shims zu welcher Gruppe gehören (liegt als `shims.json` im Shim-Ordner).
+#[derive(Deserialize, Serialize, Default)]
+struct ShimManifest {
+    groups: BTreeMap<String, ShimGroup>,
+}
+
+#[derive(Deserialize, Serialize, Default)]
+struct ShimGroup {
+    /// Ordner der aktiven Version.
+    target: String,
+    /// Dateinamen der erzeugten Shims.
+    shims: Vec<String>,
+}
+
+const MANIFEST_FILE: &str = "shims.json";
+
+/// Standard-Ordner für Shims, neben der Konfiguration.
+pub fn default_dir() -> Option<PathBuf> {
+    config::data_dir().map(|d| d.join("shims"))
+}
+
+fn load_manifest(dir: &Path) -> ShimManifest {
+    File::open(dir.join(MANIFEST_FILE))
+        .ok()
+        .and_then(|f| serde_json::from_reader(BufReader::new(f)).ok())
+        .unwrap_or_default()
+}
+
+fn save_manifest(dir: &Path, manifest: &ShimManifest) -> Result<(), String> {
+    let file = File::create(dir.join(MANIFEST_FILE)).map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(BufWriter::new(file), manifest).map_err(|e| e.to_string())
+}
+
+/// Ordner der Version, auf die die Shims einer Gruppe gerade zeigen.
+pub fn active_target(dir: &Path, group: &str) -> Option<String> {
+    load_manifest(dir).groups.remove(group).map(|g| g.target)
+}
+
+/// Gruppe und Ziel-Ordner, zu denen ein Shim (Dateiname) in `dir` gehört.
+pub fn owner_of(dir: &Path, file_name: &str) -> Option<(String, String)> {
+    load_manifest(dir).groups.into_iter()
+        .find(|(_, g)| g.shims.iter().any(|s| s == file_name))
+        .map(|(group, g)| (group, g.target))
+}
+
+/// Gruppe, deren Shim für einen Befehl (Name wie in `logic::executable_names`) gerade in `dir` liegt.
+pub fn command_owner(dir: &Path, command: &str) -> Option<String> {
+    let file_name = shim_name(Path::new(command));
+    load_manifest(dir).groups.into_iter()
+        .find(|(_, g)| g.shims.iter().any(|s| if cfg!(windows) { s.eq_ignore_ascii_case(&file_name) } else { s == &file_name }))
+        .map(|(group, _)| group)
+}
+
+/// PATH mit dem Shim-Ordner und ohne die Ordner der Gruppe. Steht der Shim-Ordner noch nicht
+/// im PATH, kommt er an die Stelle, die `position` vorgibt.
+pub fn planned_path(current: &str, dir: &Path, group_versions: &[VersionEntry], position: &InsertPosition) -> String {
+    let dir_str = dir.display().to_string();
+
+    let mut parts = PathList::parse(current);
+    let previous = logic::remove_dirs(&mut parts, group_versions.iter().flat_map(|v| v.list_dirs(logic::PATH_VAR, logic::PATH_VAR)));
+    if !parts.contains(&dir_str) {
+        let index = logic::insertion_index(&parts, position, previous);
+        parts.insert(index, dir_str);
+    }
+    parts.to_string()
+}
+
+/// Shims, die eine Version bekommt, bevor etwas geschrieben wird.
+pub struct ShimPlan {
+    group: String,
+    target: String,
+    /// Dateiname des Shims und das Programm, das er startet.
+    shims: Vec<(String, PathBuf)>,
+}
+
+/// Plant die Shims für alle Programme von `entry`. Schreibt noch nichts.
+pub fn plan(group: &str, entry: &VersionEntry) -> ShimPlan {
+    let mut shims: Vec<(String, PathBuf)> = Vec::new();
+    let executables = entry.dirs().flat_map(|d| logic::list_executables(Path::new(&path_list::expand(d))));
+    for exe in executables {
+        let name = shim_name(&exe);
+        // Wie im PATH gewinnt bei gleichem Namen der vordere Ordner der Version
+        if !shims.iter().any(|(n, _)| n == &name) {
+            shims.push((name, exe));
+        }
+    }
+    ShimPlan { group: group.to_string(), target: entry.path.clone(), shims }
+}
+
+/// Schreibt die geplanten Shims nach `dir` und entfernt die der vorherigen Versionen.
+/// Liefert die Anzahl der Shims.
+///
+/// Die neuen Shims entstehen zuerst unter einem vorläufigen Namen. Schlägt das fehl, bleiben
+/// die bisherigen Shims unangetastet. Das Manifest wird in jedem Fall gespeichert und nennt
+/// nur Dateien, die es auch gibt.
+pub fn apply(dir: &Path, plans: &[ShimPlan]) -> Result<usize, String> {
+    fs::create_dir_all(dir).map_err(|e| format!("{}: {}", dir.display(), e))?;
+
+    let mut temps = Vec::new();
+    for (name, exe) in plans.iter().flat_map(|p| &p.shims) {
+        let temp = dir.join(temp_name(name));
+        let written = write_shim(&temp, exe);
+        temps.push(temp);
+        if let Err(e) = written {
+            for temp in &temps {
+                let _ = fs::remove_file(temp);
+            }
+            return Err(e);
+        }
+    }
+
+    let mut manifest = load_manifest(dir);
+    let installed = install(dir, plans, &mut manifest);
+    // Nach einem Fehler beim Umbenennen bleiben sonst vorläufige Dateien liegen
+    for temp in &temps {
+        let _ = fs::remove_file(temp);
+    }
+    let saved = save_manifest(dir, &manifest);
+    let count = installed?;
+    saved?;
+    Ok(count)
+}
+
+fn temp_name(name: &str) -> String {
+    format!(".{}.tmp", name)
+}
+
+/// Benennt die vorläufigen Shims um und räumt die alten Shims der Gruppen auf.
+/// `manifest` wird dabei mitgeführt, auch wenn unterwegs ein Fehler auftritt.
+fn install(dir: &Path, plans: &[ShimPlan], manifest: &mut ShimManifest) -> Result<usize, String> {
+    let mut count = 0;
+    for plan in plans {
+        let old = manifest.groups.remove(&plan.group);
+        let mut installed: Vec<String> = Vec::new();
+        let mut failed = None;
+        for (name, _) in &plan.shims {
+            let shim = dir.join(name);
+            if let Err(e) = fs::rename(dir.join(temp_name(name)), &shim) {
+                failed = Some(format!("{}: {}", shim.display(), e));
+                break;
+            }
+            // Gehört der Name bisher einer anderen Gruppe, übernimmt ihn die zuletzt aktivierte
+            for other in manifest.groups.values_mut() {
+                other.shims.retain(|s| s != name);
+            }
+            installed.push(name.clone());
+        }
+
+        let old_shims = old.as_ref().map(|g| g.shims.clone()).unwrap_or_default();
+        if let Some(e) = failed {
+            // Die alte Version bleibt eingetragen, mit allen Shims, die es jetzt gibt
+            if let Some(mut group) = old {
+                group.shims.extend(installed.into_iter().filter(|n| !old_shims.contains(n)));
+                manifest.groups.insert(plan.group.clone(), group);
+            }
+            return Err(e);
+        }
+
+        for stale in old_shims.iter().filter(|n| !installed.contains(n)) {
+            // Inzwischen vielleicht von einer anderen Gruppe übernommen
+            if !manifest.groups.values().any(|g| g.shims.contains(stale)) {
+                let _ = fs::remove_file(dir.join(stale));
+            }
+        }
+        count += installed.len();
+        manifest.groups.insert(plan.group.clone(), ShimGroup { target: plan.target.clone(), shims: installed });
+    }
+    Ok(count)
+}
+
+#[cfg(windows)]
+fn shim_name(exe: &Path) -> String {
+    let stem = exe.file_stem().unwrap_or_default().to_string_lossy();
+    format!("{}.cmd", stem)
+}
+
+#[cfg(not(windows))]
+fn shim_name(exe: &Path) -> String {
+    exe.file_name().unwrap_or_default().to_string_lossy().to_string()
+}
+
+#[cfg(windows)]
+fn write_shim(shim: &Path, exe: &Path) -> Result<(), String> {
+    let content = format!("@echo off\r\ncall \"{}\" %*\r\nexit /b %errorlevel%\r\n", exe.display());
+    fs::write(shim, content).map_err(|e| format!("{}: {}", shim.display(), e))
+}
+
+#[cfg(not(windows))]
+fn write_shim(shim: &Path, exe: &Path) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let target = exe.display().to_string().replace('\'', "'\\''");
+    let content = format!("#!/bin/sh\nexec '{}' \"$@\"\n", target);
+    fs::write(shim, content).map_err(|e| format!("{}: {}", shim.display(), e))?;
+    fs::set_permissions(shim, fs::Permissions::from_mode(0o755)).map_err(|e| format!("{}: {}", shim.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shim_plan(group: &str, target: &str, names: &[&str]) -> ShimPlan {
+        ShimPlan {
+            group: group.to_string(),
+            target: target.to_string(),
+            shims: names.iter().map(|n| (n.to_string(), Path::new(target).join(n))).collect(),
+        }
+    }
+
+    fn shims_of(dir: &Path, group: &str) -> Vec<String> {
+        load_manifest(dir).groups.remove(group).map(|g| g.shims).unwrap_or_default()
+    }
+
+    fn points_to(dir: &Path, name: &str, target: &str) -> bool {
+        fs::read_to_string(dir.join(name)).is_ok_and(|c| c.contains(target))
+    }
+
+    #[test]
+    fn switching_replaces_the_shims_of_the_group() {
+        let dir = tempfile::tempdir().unwrap();
+        apply(dir.path(), &[shim_plan("node", "/opt/node18", &["node", "npm"])]).unwrap();
+
+        let count = apply(dir.path(), &[shim_plan("node", "/opt/node20", &["node"])]).unwrap();
+
+        assert_eq!(count, 1);
+        assert_eq!(active_target(dir.path(), "node").as_deref(), Some("/opt/node20"));
+        assert_eq!(shims_of(dir.path(), "node"), ["node"]);
+        assert!(points_to(dir.path(), "node", "/opt/node20"));
+        assert!(!dir.path().join("npm").exists());
+    }
+
+    #[test]
+    fn a_name_taken_over_leaves_the_other_group() {
+        let dir = tempfile::tempdir().unwrap();
+        apply(dir.path(), &[shim_plan("python", "/opt/py", &["python", "tool"])]).unwrap();
+
+        apply(dir.path(), &[shim_plan("node", "/opt/node", &["node", "tool"])]).unwrap();
+
+        assert_eq!(shims_of(dir.path(), "python"), ["python"]);
+        assert_eq!(owner_of(dir.path(), "tool"), Some(("node".to_string(), "/opt/node".to_string())));
+        assert!(points_to(dir.path(), "tool", "/opt/node"));
+    }
+
+    #[test]
+    fn shared_name_survives_cleanup_of_the_previous_owner() {
+        let dir = tempfile::tempdir().unwrap();
+        apply(dir.path(), &[shim_plan("python", "/opt/py", &["python", "tool"])]).unwrap();
+
+        // Python verliert `tool` an Node und schaltet in derselben Aktivierung um
+        apply(dir.path(), &[shim_plan("node", "/opt/node", &["tool"]), shim_plan("python", "/opt/py2", &["python"])]).unwrap();
+
+        assert!(points_to(dir.path(), "tool", "/opt/node"));
+        assert_eq!(shims_of(dir.path(), "node"), ["tool"]);
+    }
+
+    #[test]
+    fn failed_write_keeps_the_previous_shims() {
+        let dir = tempfile::tempdir().unwrap();
+        apply(dir.path(), &[shim_plan("node", "/opt/node18", &["node", "npm"])]).unwrap();
+        // Ein Ordner, wo der vorläufige Shim hin soll, lässt das Schreiben scheitern
+        fs::create_dir(dir.path().join(temp_name("npm"))).unwrap();
+
+        assert!(apply(dir.path(), &[shim_plan("node", "/opt/node20", &["node", "npm"])]).is_err());
+
+        assert_eq!(active_target(dir.path(), "node").as_deref(), Some("/opt/node18"));
+        for name in shims_of(dir.path(), "node") {
+            assert!(points_to(dir.path(), &name, "/opt/node18"), "{}", name);
+        }
+        assert!(!dir.path().join(temp_name("node")).exists());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn plan_takes_the_first_program_of_each_name() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        for sub in ["bin", "scripts"] {
+            fs::create_dir(dir.path().join(sub)).unwrap();
+            let exe = dir.path().join(sub).join("tool");
+            fs::write(&exe, "#!/bin/sh\n").unwrap();
+            fs::set_permissions(&exe, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        let bin = dir.path().join("bin").display().to_string();
+        let entry = VersionEntry {
+            path: bin.clone(),
+            extra_paths: vec![dir.path().join("scripts").display().to_string()],
+            ..Default::default()
+        };
+
+        let plan = plan("tools", &entry);
+
+        assert_eq!(plan.target, bin);
+        assert_eq!(plan.shims, [("tool".to_string(), dir.path().join("bin").join("tool"))]);
+    }
+}
@@ -0,0 +1,148 @@
+use chrono::Local;
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+
+use crate::backend::EnvBackend;
+use crate::logic;
+use crate::user_env;
+
+const SNAPSHOT_FILE: &str = "path_snapshots.json";
+/// Ältere Stände fallen heraus, damit die Datei nicht endlos wächst.
+const MAX_SNAPSHOTS: usize = 50;
+
+/// Eine Listen-Variable (meist PATH), wie sie vor einer Änderung aussah.
+#[derive(Deserialize, Serialize, Clone, Debug)]
+pub struct Snapshot {
+    /// z.B. "2024-05-01 14:30:05"
+    pub time: String,
+    /// Ältere Snapshots kennen nur den PATH.
+    #[serde(default = "path_var")]
+    pub var: String,
+    /// Was danach geändert wurde, z.B. "Switch" oder "Cleanup".
+    pub reason: String,
+    pub value: String,
+    /// Ob es die Variable vorher gab. Ältere Snapshots gelten als vorhanden.
+    #[serde(default = "existed")]
+    pub existed: bool,
+    /// Art des Werts, nur bei Snapshots aus dem Variablen-Editor.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expandable: Option<bool>,
+}
+
+fn path_var() -> String {
+    logic::PATH_VAR.to_string()
+}
+
+fn existed() -> bool {
+    true
+}
+
+fn snapshot_path(backend: &dyn EnvBackend) -> Option<PathBuf> {
+    backend.data_dir().map(|d| d.join(SNAPSHOT_FILE))
+}
+
+/// Alle gespeicherten Stände des Backends, der neueste zuerst.
+pub fn load(backend: &dyn EnvBackend) -> Vec<Snapshot> {
+    snapshot_path(backend)
+        .and_then(|p| File::open(p).ok())
+        .and_then(|f| serde_json::from_reader(BufReader::new(f)).ok())
+        .unwrap_or_default()
+}
+
+fn save(path: &Path, snapshots: &[Snapshot]) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+    }
+    let file = File::create(path).map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(BufWriter::new(file), snapshots).map_err(|e| e.to_string())
+}
+
+/// Merkt sich `value` als neuesten Stand von `var`.
+pub fn record(backend: &dyn EnvBackend, var: &str, reason: &str, value: &str) -> Result<(), String> {
+    push(backend, var, reason, value, backend.read_var(var).is_some(), None)
+}
+
+/// Wie `record`, merkt sich aber auch, ob der Wert erweiterbar war.
+/// `value` ist `None`, wenn es die Variable noch nicht gab.
+pub fn record_kind(backend: &dyn EnvBackend, var: &str, reason: &str, value: Option<&str>, expandable: bool) -> Result<(), String> {
+    push(backend, var, reason, value.unwrap_or_default(), value.is_some(), Some(expandable))
+}
+
+fn push(backend: &dyn EnvBackend, var: &str, reason: &str, value: &str, existed: bool, expandable: Option<bool>) -> Result<(), String> {
+    let Some(path) = snapshot_path(backend) else { return Ok(()) };
+    let mut snapshots = load(backend);
+    snapshots.insert(0, Snapshot {
+        time: Local::now().format("%Y-%m-%d %H:%M:%S").to_string(),
+        var: var.to_string(),
+        reason: reason.to_string(),
+        value: value.to_string(),
+        existed,
+        expandable,
+    });
+    snapshots.truncate(MAX_SNAPSHOTS);
+    save(&path, &snapshots)
+}
+
+/// Schreibt einen gespeicherten Stand zurück. Der aktuelle Wert wird dabei selbst
+/// wieder gesichert, das Zurücksetzen lässt sich also auch rückgängig machen.
+pub fn restore(backend: &dyn EnvBackend, snapshot: &Snapshot) -> Result<(), String> {
+    let reason = format!("Restore {}", snapshot.time);
+    match snapshot.expandable {
+        // Aus dem Variablen-Editor: Gab es die Variable vorher nicht, wird sie wieder gelöscht
+        Some(_) if !snapshot.existed => user_env::remove_var(backend, &snapshot.var, &reason),
+        Some(expandable) => user_env::set_var(backend, &snapshot.var, &snapshot.value, expandable, &reason),
+        None => logic::set_list_var(backend, &snapshot.var, snapshot.value.clone(), &reason),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::MemoryBackend;
+
+    fn snapshot(var: &str, value: &str, existed: bool, expandable: Option<bool>) -> Snapshot {
+        Snapshot {
+            time: "2024-05-01 14:30:05".to_string(),
+            var: var.to_string(),
+            reason: "Test".to_string(),
+            value: value.to_string(),
+            existed,
+            expandable,
+        }
+    }
+
+    fn backend(vars: &[(&str, &str)]) -> MemoryBackend {
+        MemoryBackend::with_vars(vars.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect())
+    }
+
+    #[test]
+    fn restore_keeps_an_empty_variable_that_existed() {
+        let backend = backend(&[("EDITOR", "vim")]);
+        restore(&backend, &snapshot("EDITOR", "", true, Some(false))).unwrap();
+        assert_eq!(backend.read_var("EDITOR").as_deref(), Some(""));
+    }
+
+    #[test]
+    fn restore_removes_a_variable_that_did_not_exist() {
+        let backend = backend(&[("EDITOR", "vim")]);
+        restore(&backend, &snapshot("EDITOR", "", false, Some(false))).unwrap();
+        assert_eq!(backend.read_var("EDITOR"), None);
+    }
+
+    #[test]
+    fn restore_writes_back_a_list_variable() {
+        let backend = backend(&[(logic::PATH_VAR, "/new")]);
+        restore(&backend, &snapshot(logic::PATH_VAR, "/old", true, None)).unwrap();
+        assert_eq!(logic::get_current_path_var(&backend), "/old");
+    }
+
+    #[test]
+    fn older_snapshots_count_as_existing() {
+        let json = r#"{"time": "2024-05-01 14:30:05", "reason": "Switch", "value": "/bin"}"#;
+        let snapshot: Snapshot = serde_json::from_str(json).unwrap();
+        assert!(snapshot.existed);
+        assert_eq!(snapshot.var, logic::PATH_VAR);
+    }
+}
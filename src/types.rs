@@ -1,27 +1,336 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
 
-#[derive(Deserialize, Serialize, Clone, Debug)]
+use crate::env_vars;
+use crate::logic;
+use crate::path_list::{self, PathList};
+
+/// Eine Version innerhalb einer Gruppe: ein oder mehrere Ordner, die beim Aktivieren
+/// gemeinsam in den PATH kommen.
+#[derive(Deserialize, Serialize, Clone, Debug, Default)]
 pub struct VersionEntry {
+    /// Hauptordner der Version (z.B. mit `python.exe`).
     pub path: String,
     pub alias: String,
+    /// Weitere Ordner, die direkt hinter `path` stehen (z.B. `python\Scripts`).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extra_paths: Vec<String>,
+    /// Ordner für weitere Listen-Variablen (z.B. `PYTHONPATH`), die beim Aktivieren mitgesetzt werden.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub lists: BTreeMap<String, Vec<String>>,
+    /// Zusätzliche Variablen (z.B. `JAVA_HOME`), die beim Aktivieren gesetzt werden.
+    /// `${path}` im Wert wird durch den Hauptordner der Version ersetzt.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub env: BTreeMap<String, String>,
+    /// Befehle, die vor und nach dem Aktivieren dieser Version laufen.
+    #[serde(default, skip_serializing_if = "Hooks::is_empty")]
+    pub hooks: Hooks,
+}
+
+impl VersionEntry {
+    /// Alle Ordner der Version in PATH-Reihenfolge.
+    pub fn dirs(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.path.as_str()).chain(self.extra_paths.iter().map(String::as_str))
+    }
+
+    /// Ob `dir` einer der Ordner der Version ist (auch anders geschrieben).
+    pub fn has_dir(&self, dir: &str) -> bool {
+        self.dirs().any(|d| path_list::same_entry(d, dir))
+    }
+
+    /// Die Ordner als ein Wert mit dem PATH-Trennzeichen, z.B. zum Bearbeiten.
+    pub fn dirs_string(&self) -> String {
+        self.dirs().map(str::to_string).collect::<PathList>().to_string()
+    }
+
+    /// Übernimmt die Ordner aus einem Wert mit dem PATH-Trennzeichen. Der erste wird `path`.
+    pub fn set_dirs(&mut self, value: &str) {
+        let dirs = PathList::parse(value);
+        self.path = dirs.iter().next().cloned().unwrap_or_default();
+        self.extra_paths = dirs.iter().skip(1).cloned().collect();
+    }
+
+    /// Die weiteren Listen als Zeilen `VAR=ordner1;ordner2`, z.B. zum Bearbeiten.
+    pub fn lists_string(&self) -> String {
+        self.lists.iter()
+            .map(|(var, dirs)| format!("{}={}", var, dirs.iter().cloned().collect::<PathList>()))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Gegenstück zu [`lists_string`](Self::lists_string). Leere Listen fallen weg.
+    pub fn set_lists(&mut self, text: &str) {
+        self.lists = env_vars::parse_lines(text).into_iter()
+            .map(|(var, value)| (var, PathList::parse(&value).iter().cloned().collect::<Vec<_>>()))
+            .filter(|(_, dirs)| !dirs.is_empty())
+            .collect();
+    }
+
+    /// Ordner der Version für die Variable `var`. In der Variable der Gruppe (`target_var`)
+    /// stehen zuerst die eigenen Ordner der Version.
+    pub fn list_dirs(&self, var: &str, target_var: &str) -> Vec<&str> {
+        let own = logic::same_var(var, target_var).then(|| self.dirs()).into_iter().flatten();
+        let extra = self.lists.iter()
+            .filter(|(name, _)| logic::same_var(name, var))
+            .flat_map(|(_, dirs)| dirs.iter().map(String::as_str));
+        own.chain(extra).collect()
+    }
+
+    /// Ordner der Version, die es auf der Festplatte nicht gibt.
+    /// In den weiteren Listen dürfen auch Dateien stehen (z.B. `.jar` im CLASSPATH).
+    pub fn missing_dirs(&self) -> Vec<&str> {
+        let dirs = self.dirs().filter(|d| !Path::new(&path_list::expand(d)).is_dir());
+        let others = self.lists.values().flatten().map(String::as_str)
+            .filter(|d| !Path::new(&path_list::expand(d.trim_end_matches('*'))).exists());
+        dirs.chain(others).collect()
+    }
+}
+
+/// Wie eine Version aktiviert wird.
+#[derive(Deserialize, Serialize, Clone, Copy, Debug, PartialEq, Default)]
+pub enum SwitchMode {
+    /// Der Ordner der Version wird vorne in den PATH geschrieben.
+    #[default]
+    Path,
+    /// Ein fester Shim-Ordner steht im PATH, nur die Shims darin werden neu geschrieben.
+    Shims,
+}
+
+/// Wo der Ordner einer Gruppe beim Aktivieren in den PATH kommt.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq, Default)]
+pub enum InsertPosition {
+    /// Ganz nach vorne, die Version gewinnt gegen alles andere.
+    #[default]
+    Prepend,
+    /// Ans Ende, alles andere im PATH hat Vorrang.
+    Append,
+    /// An die Stelle der bisherigen Version der Gruppe (sonst nach vorne).
+    Replace,
+    /// Direkt vor einen anderen Eintrag (fehlt er, nach vorne).
+    Before(String),
+    /// Direkt hinter einen anderen Eintrag (fehlt er, nach vorne).
+    After(String),
+}
+
+/// Befehle rund um das Aktivieren einer Version, z.B. `npm config set prefix ${path}`.
+/// `${path}` wird wie bei den Variablen durch den Hauptordner der Version ersetzt.
+#[derive(Deserialize, Serialize, Clone, Debug, Default, PartialEq)]
+#[serde(default)]
+pub struct Hooks {
+    /// Laufen vorher. Schlägt einer fehl, wird nicht umgeschaltet.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub pre: Vec<String>,
+    /// Laufen nach erfolgreichem Umschalten mit der neuen Umgebung.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub post: Vec<String>,
+}
+
+impl Hooks {
+    pub fn is_empty(&self) -> bool {
+        self.pre.is_empty() && self.post.is_empty()
+    }
+
+    /// Als Zeilen `pre: Befehl` bzw. `post: Befehl`, z.B. zum Bearbeiten.
+    pub fn to_lines(&self) -> String {
+        let pre = self.pre.iter().map(|c| format!("pre: {}", c));
+        let post = self.post.iter().map(|c| format!("post: {}", c));
+        pre.chain(post).collect::<Vec<_>>().join("\n")
+    }
+
+    /// Gegenstück zu [`to_lines`](Self::to_lines). Andere Zeilen werden ignoriert.
+    pub fn parse_lines(text: &str) -> Self {
+        let mut hooks = Hooks::default();
+        for line in text.lines() {
+            let Some((phase, command)) = line.split_once(':') else { continue };
+            let command = command.trim().to_string();
+            if command.is_empty() {
+                continue;
+            }
+            match phase.trim() {
+                "pre" => hooks.pre.push(command),
+                "post" => hooks.post.push(command),
+                _ => {}
+            }
+        }
+        hooks
+    }
+}
+
+/// Wohin eine Gruppe ihre Ordner schreibt: in welche Variable und an welche Stelle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GroupTarget {
+    pub var: String,
+    pub position: InsertPosition,
+}
+
+impl Default for GroupTarget {
+    fn default() -> Self {
+        Self { var: logic::PATH_VAR.to_string(), position: InsertPosition::default() }
+    }
+}
+
+impl GroupTarget {
+    /// Ob die Gruppe den PATH verwaltet (nur dann gibt es Programme und Shims).
+    pub fn is_path(&self) -> bool {
+        logic::same_var(&self.var, logic::PATH_VAR)
+    }
 }
 
+impl InsertPosition {
+    pub fn describe(&self) -> String {
+        match self {
+            InsertPosition::Prepend => "prepend".to_string(),
+            InsertPosition::Append => "append".to_string(),
+            InsertPosition::Replace => "replace".to_string(),
+            InsertPosition::Before(anchor) => format!("before {}", anchor),
+            InsertPosition::After(anchor) => format!("after {}", anchor),
+        }
+    }
+}
+
+/// Art eines Problems, das der Cleaner im PATH gefunden hat.
 #[derive(Clone, Debug, PartialEq)]
 pub enum IssueType {
     Missing,
     Duplicate,
+    /// Derselbe Ordner wie der frühere Eintrag `of`, nur anders geschrieben (z.B. `/` am Ende).
+    Variant { of: String },
+    /// Zeigt auf eine Datei. `parent` ist ihr Ordner, falls es ihn gibt.
+    NotADirectory { parent: Option<String> },
+    /// Der Ordner ist leer.
+    EmptyDir,
+    /// Der Ordner enthält keine ausführbaren Dateien.
+    NoExecutables,
+    /// Verweise wie `%FOO%` oder `$FOO`, deren Variable nicht gesetzt ist.
+    UnresolvedVar { names: Vec<String> },
+    /// Relativer Pfad, hängt vom aktuellen Verzeichnis ab. Wird nur gemeldet, nicht bereinigt.
+    Relative,
+    /// Die aktive Version von `group` bietet Programme an, die die aktive Version von `by` verdeckt.
+    /// Wird nur gemeldet, nicht bereinigt.
+    Shadowed { group: String, by: String, names: Vec<String> },
+    /// Von der Version `alias` aus `group` stehen nur einige Ordner im PATH, `missing` fehlen.
+    Incomplete { group: String, alias: String, missing: Vec<String> },
 }
 
+/// Was der Cleaner mit einem ausgewählten Eintrag macht.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CleanerFix {
+    Remove,
+    /// Der erste Eintrag bleibt, spätere Schreibweisen desselben Ordners werden entfernt.
+    KeepFirst,
+    Replace(String),
+    /// Die Ordner werden direkt hinter dem Eintrag eingefügt.
+    InsertAfter(Vec<String>),
+}
+
+impl IssueType {
+    /// Wie das Problem behoben wird. `None`: nur ein Hinweis.
+    pub fn fix(&self) -> Option<CleanerFix> {
+        match self {
+            IssueType::Duplicate | IssueType::Variant { .. } => Some(CleanerFix::KeepFirst),
+            IssueType::NotADirectory { parent: Some(parent) } => Some(CleanerFix::Replace(parent.clone())),
+            IssueType::Shadowed { .. } | IssueType::Relative => None,
+            IssueType::Incomplete { missing, .. } => Some(CleanerFix::InsertAfter(missing.clone())),
+            _ => Some(CleanerFix::Remove),
+        }
+    }
+
+    /// Ob der Eintrag nach dem Scan schon ausgewählt ist. Leere Ordner oder solche ohne
+    /// Programme (z.B. nur DLLs) sind oft Absicht und müssen bewusst gewählt werden,
+    /// ebenso Verweise auf Variablen, die vielleicht erst später gesetzt werden.
+    pub fn selected_by_default(&self) -> bool {
+        self.fix().is_some()
+            && !matches!(self, IssueType::EmptyDir | IssueType::NoExecutables | IssueType::UnresolvedVar { .. })
+    }
+}
+
+/// Ein problematischer Eintrag in `var` (meist PATH). Nur ausgewählte Einträge werden bereinigt.
 #[derive(Clone, Debug)]
 pub struct CleanerEntry {
+    pub var: String,
     pub path: String,
     pub issue: IssueType,
     pub selected: bool,
 }
 
+/// Eine Zeile im Vergleich zweier PATH-Werte.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathChange {
+    Kept(String),
+    Added(String),
+    Removed(String),
+    /// Steht in beiden Werten, aber an anderer Stelle.
+    Moved(String),
+}
+
+/// Hinweis zu einem geplanten PATH, bevor er geschrieben wird.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PathWarning {
+    /// Der neue Wert ist länger als das, was manche Programme verarbeiten.
+    TooLong { length: usize, limit: usize },
+    /// Der neue Wert ist länger, als das System erlaubt, und wird nicht geschrieben.
+    ExceedsLimit { length: usize, limit: usize },
+    /// `name` aus `dir` wird nicht gefunden, weil `by` weiter vorne dasselbe Programm hat.
+    Shadowed { name: String, dir: String, by: String },
+}
+
+impl PathWarning {
+    pub fn describe(&self) -> String {
+        match self {
+            PathWarning::TooLong { length, limit } => {
+                format!("PATH is {} characters long (limit {})", length, limit)
+            }
+            PathWarning::ExceedsLimit { length, limit } => {
+                format!("The value is {} characters long, more than the maximum of {}. It will not be written", length, limit)
+            }
+            PathWarning::Shadowed { name, dir, by } => {
+                format!("{} from {} is shadowed by {}", name, dir, by)
+            }
+        }
+    }
+}
+
 // NEU: Eintrag für den Verlauf
 #[derive(Deserialize, Serialize, Clone, Debug)]
 pub struct HistoryEntry {
     pub time: String,    // z.B. "14:30:05"
     pub message: String, // z.B. "Activated Python 3.11"
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hooks_round_trip_through_lines() {
+        let hooks = Hooks {
+            pre: vec!["echo ${path}".to_string(), "test -d /opt".to_string()],
+            post: vec!["npm config set prefix ${path}".to_string()],
+        };
+        let lines = hooks.to_lines();
+        assert_eq!(lines, "pre: echo ${path}\npre: test -d /opt\npost: npm config set prefix ${path}");
+        assert_eq!(Hooks::parse_lines(&lines), hooks);
+    }
+
+    #[test]
+    fn hooks_parsing_skips_unknown_and_empty_lines() {
+        let hooks = Hooks::parse_lines("  pre :  echo a:b  \nother: x\npost:\nno colon\n\npost: done");
+        assert_eq!(hooks.pre, ["echo a:b"]);
+        assert_eq!(hooks.post, ["done"]);
+        assert!(Hooks::parse_lines("").is_empty());
+    }
+
+    #[test]
+    fn relative_entries_are_only_reported() {
+        assert_eq!(IssueType::Relative.fix(), None);
+        assert!(!IssueType::Relative.selected_by_default());
+    }
+
+    #[test]
+    fn unresolved_variables_are_not_preselected() {
+        let issue = IssueType::UnresolvedVar { names: vec!["NOPE".to_string()] };
+        assert_eq!(issue.fix(), Some(CleanerFix::Remove));
+        assert!(!issue.selected_by_default());
+        assert!(IssueType::Missing.selected_by_default());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::backend::EnvBackend;
+use crate::logic;
+use crate::snapshots;
+
+/// Eine Umgebungsvariable des Benutzers, wie sie der Variablen-Editor zeigt.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct UserVar {
+    pub name: String,
+    pub value: String,
+    /// Verweise wie `%USERPROFILE%` bzw. `$HOME` werden vom System aufgelöst.
+    pub expandable: bool,
+}
+
+impl UserVar {
+    pub fn describe(&self) -> String {
+        let kind = if self.expandable { "expandable" } else { "literal" };
+        format!("{} ({})", self.name, kind)
+    }
+}
+
+/// Alle Variablen des Benutzers, nach Namen sortiert.
+pub fn list(backend: &dyn EnvBackend) -> Vec<UserVar> {
+    let mut vars: Vec<UserVar> = backend.var_names().into_iter()
+        .filter_map(|name| {
+            let value = backend.read_var(&name)?;
+            let expandable = backend.is_expandable(&name);
+            Some(UserVar { name, value, expandable })
+        })
+        .collect();
+    vars.sort_by_key(|v| v.name.to_lowercase());
+    vars
+}
+
+/// Prüft einen Variablennamen. Unter Windows ist fast alles außer `=` erlaubt,
+/// die Shells unter Unix kennen nur Buchstaben, Ziffern und `_`.
+pub fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("The variable name is empty".to_string());
+    }
+    let valid = if cfg!(windows) {
+        name.len() <= 255 && !name.contains('=') && !name.chars().any(char::is_control)
+    } else {
+        name.chars().next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+            && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+    };
+    if !valid {
+        return Err(format!("Invalid variable name: {}", name));
+    }
+    Ok(())
+}
+
+/// Prüft einen Wert: keine Zeilenumbrüche und nicht länger als das Limit des Systems.
+pub fn validate_value(value: &str) -> Result<(), String> {
+    if value.contains(['\n', '\r', '\0']) {
+        return Err("The value must not contain line breaks".to_string());
+    }
+    let length = logic::path_length(value);
+    if length > logic::PATH_HARD_LIMIT {
+        return Err(format!("The value is {} characters long, the limit is {}", length, logic::PATH_HARD_LIMIT));
+    }
+    Ok(())
+}
+
+/// Setzt eine Variable. Der alte Stand (mit Art) wird vorher als Snapshot gesichert.
+pub fn set_var(backend: &dyn EnvBackend, name: &str, value: &str, expandable: bool, reason: &str) -> Result<(), String> {
+    validate_name(name)?;
+    validate_value(value)?;
+    let old = backend.read_var(name);
+    let old_expandable = backend.is_expandable(name);
+    if old.as_deref() == Some(value) && old_expandable == expandable {
+        return Ok(());
+    }
+    snapshots::record_kind(backend, name, reason, old.as_deref(), old_expandable)?;
+    backend.write_var_as(name, value, expandable)?;
+    backend.broadcast_change();
+    Ok(())
+}
+
+/// Löscht eine Variable. Der alte Stand wird vorher als Snapshot gesichert.
+pub fn remove_var(backend: &dyn EnvBackend, name: &str, reason: &str) -> Result<(), String> {
+    let Some(old) = backend.read_var(name) else { return Ok(()) };
+    snapshots::record_kind(backend, name, reason, Some(&old), backend.is_expandable(name))?;
+    backend.remove_var(name)?;
+    backend.broadcast_change();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::FileBackend;
+
+    #[test]
+    fn invalid_names_are_rejected() {
+        assert!(validate_name("").is_err());
+        assert!(validate_name("A=B").is_err());
+        assert!(validate_name("JAVA_HOME").is_ok());
+        if !cfg!(windows) {
+            assert!(validate_name("1ST").is_err());
+            assert!(validate_name("MY-VAR").is_err());
+        }
+    }
+
+    #[test]
+    fn over_long_or_multi_line_values_are_rejected() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FileBackend::new(dir.path().join("env.json"));
+
+        assert!(set_var(&backend, "LONG", &"x".repeat(logic::PATH_HARD_LIMIT + 1), false, "Test").is_err());
+        assert!(set_var(&backend, "LINES", "a\nb", false, "Test").is_err());
+        assert!(backend.var_names().is_empty());
+        assert!(snapshots::load(&backend).is_empty());
+    }
+
+    #[test]
+    fn set_and_remove_record_the_previous_state() {
+        let dir = tempfile::tempdir().unwrap();
+        let backend = FileBackend::new(dir.path().join("env.json"));
+
+        set_var(&backend, "EDITOR", "vim", false, "Edit variable").unwrap();
+        set_var(&backend, "EDITOR", "nano", false, "Edit variable").unwrap();
+        remove_var(&backend, "EDITOR", "Delete variable").unwrap();
+
+        assert_eq!(backend.read_var("EDITOR"), None);
+        let snapshots = snapshots::load(&backend);
+        let states: Vec<(&str, &str, bool)> = snapshots.iter().map(|s| (s.reason.as_str(), s.value.as_str(), s.existed)).collect();
+        assert_eq!(states, [("Delete variable", "nano", true), ("Edit variable", "vim", true), ("Edit variable", "", false)]);
+        assert!(snapshots.iter().all(|s| s.var == "EDITOR" && s.expandable == Some(false)));
+    }
+}
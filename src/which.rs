@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use crate::layers::LayeredEntry;
+use crate::logic;
+use crate::path_list;
+use crate::shims;
+use crate::types::VersionEntry;
+
+/// Zu welcher konfigurierten Version ein Treffer gehört.
+#[derive(Clone, Debug)]
+pub struct Owner {
+    pub group: String,
+    pub alias: String,
+    /// Der Treffer ist ein Shim, der auf diese Version zeigt.
+    pub via_shim: bool,
+}
+
+/// Eine Datei, die beim Aufruf eines Befehls gefunden wird. Der erste Treffer gewinnt.
+#[derive(Clone, Debug)]
+pub struct WhichMatch {
+    pub file: PathBuf,
+    pub source: LayeredEntry,
+    pub owner: Option<Owner>,
+}
+
+/// Dateinamen, unter denen ein Befehl gesucht wird. Unter Windows mit den Endungen aus `PATHEXT`,
+/// außer der Befehl hat schon eine davon.
+pub fn candidate_names(command: &str) -> Vec<String> {
+    if !cfg!(windows) {
+        return vec![command.to_string()];
+    }
+    let pathext = std::env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+    let exts: Vec<String> = pathext.split(';').filter(|e| !e.is_empty()).map(|e| e.to_lowercase()).collect();
+
+    let lower = command.to_lowercase();
+    if exts.iter().any(|e| lower.ends_with(e.as_str())) {
+        return vec![command.to_string()];
+    }
+    exts.iter().map(|e| format!("{}{}", command, e)).collect()
+}
+
+fn is_match(path: &Path) -> bool {
+    // Unter Windows entscheidet PATHEXT, nicht die feste Liste aus `logic::is_executable`
+    if cfg!(windows) { path.is_file() } else { logic::is_executable(path) }
+}
+
+/// Alle Treffer für `command` im wirksamen PATH, in der Reihenfolge, in der gesucht wird.
+pub fn resolve(
+    effective: &[LayeredEntry],
+    command: &str,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+) -> Vec<WhichMatch> {
+    resolve_with_shims(effective, command, languages, shims::default_dir().as_deref())
+}
+
+fn resolve_with_shims(
+    effective: &[LayeredEntry],
+    command: &str,
+    languages: &HashMap<String, Vec<VersionEntry>>,
+    shim_dir: Option<&Path>,
+) -> Vec<WhichMatch> {
+    let names = candidate_names(command);
+    let mut matches = Vec::new();
+
+    for source in effective {
+        let dir = PathBuf::from(path_list::expand(&source.entry));
+        for name in &names {
+            let file = dir.join(name);
+            if !is_match(&file) {
+                continue;
+            }
+            let is_shim_dir = shim_dir.is_some_and(|d| path_list::same_entry(&d.display().to_string(), &source.entry));
+            let owner = if is_shim_dir {
+                shim_owner(&dir, name, languages)
+            } else {
+                dir_owner(&source.entry, languages)
+            };
+            matches.push(WhichMatch { file, source: source.clone(), owner });
+        }
+    }
+    matches
+}
+
+fn dir_owner(entry: &str, languages: &HashMap<String, Vec<VersionEntry>>) -> Option<Owner> {
+    languages.iter().find_map(|(group, versions)| {
+        versions.iter()
+            .find(|v| v.has_dir(entry))
+            .map(|v| Owner { group: group.clone(), alias: v.alias.clone(), via_shim: false })
+    })
+}
+
+fn shim_owner(dir: &Path, file_name: &str, languages: &HashMap<String, Vec<VersionEntry>>) -> Option<Owner> {
+    let (group, target) = shims::owner_of(dir, file_name)?;
+    let alias = languages.get(&group)?.iter().find(|v| path_list::same_entry(&v.path, &target))?.alias.clone();
+    Some(Owner { group, alias, via_shim: true })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layers::PathLayer;
+
+    /// Ordner mit einer ausführbaren Datei `tool`.
+    fn tool_dir(parent: &Path, name: &str) -> PathBuf {
+        let dir = parent.join(name);
+        std::fs::create_dir(&dir).unwrap();
+        #[cfg(windows)]
+        std::fs::write(dir.join("tool.exe"), "").unwrap();
+        #[cfg(not(windows))]
+        {
+            use std::os::unix::fs::PermissionsExt;
+
+            std::fs::write(dir.join("tool"), "#!/bin/sh\n").unwrap();
+            std::fs::set_permissions(dir.join("tool"), std::fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        dir
+    }
+
+    fn user_entry(dir: &Path) -> LayeredEntry {
+        LayeredEntry { layer: PathLayer::User, entry: dir.display().to_string() }
+    }
+
+    fn version(dir: &Path, alias: &str) -> VersionEntry {
+        VersionEntry { path: dir.display().to_string(), alias: alias.to_string(), ..Default::default() }
+    }
+
+    #[test]
+    fn first_dir_in_path_wins() {
+        let tmp = tempfile::tempdir().unwrap();
+        let first = tool_dir(tmp.path(), "first");
+        let second = tool_dir(tmp.path(), "second");
+        let languages = HashMap::from([("Tools".to_string(), vec![version(&second, "2")])]);
+
+        let matches = resolve_with_shims(&[user_entry(&first), user_entry(&second)], "tool", &languages, None);
+
+        assert_eq!(matches.len(), 2);
+        assert!(matches[0].file.starts_with(&first));
+        assert!(matches[0].owner.is_none());
+        let owner = matches[1].owner.as_ref().unwrap();
+        assert_eq!((owner.group.as_str(), owner.alias.as_str(), owner.via_shim), ("Tools", "2", false));
+    }
+
+    #[test]
+    fn shim_belongs_to_the_version_it_points_to() {
+        let tmp = tempfile::tempdir().unwrap();
+        let shim_dir = tmp.path().join("shims");
+        let versions = vec![version(&tool_dir(tmp.path(), "v1"), "1"), version(&tool_dir(tmp.path(), "v2"), "2")];
+        shims::apply(&shim_dir, &[shims::plan("Tools", &versions[1])]).unwrap();
+        let languages = HashMap::from([("Tools".to_string(), versions)]);
+
+        let matches = resolve_with_shims(&[user_entry(&shim_dir)], "tool", &languages, Some(&shim_dir));
+
+        assert_eq!(matches.len(), 1);
+        let owner = matches[0].owner.as_ref().unwrap();
+        assert_eq!((owner.group.as_str(), owner.alias.as_str(), owner.via_shim), ("Tools", "2", true));
+    }
+}